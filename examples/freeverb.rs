@@ -0,0 +1,116 @@
+// Freeverb style reverb assembled from the comb and allpass sections: eight
+// parallel lowpass-feedback combs followed by four allpasses in series.
+// Delay lengths are the original tunings for 44.1 kHz.
+
+use ringbuffer::filter::Filter;
+use ringbuffer::reverb::{Allpass, FeedbackComb};
+
+const FIXED_GAIN: f32 = 0.015;
+
+type Combs = (
+    FeedbackComb<f32, 1116>,
+    FeedbackComb<f32, 1188>,
+    FeedbackComb<f32, 1277>,
+    FeedbackComb<f32, 1356>,
+    FeedbackComb<f32, 1422>,
+    FeedbackComb<f32, 1491>,
+    FeedbackComb<f32, 1557>,
+    FeedbackComb<f32, 1617>,
+);
+
+type Allpasses = (
+    Allpass<f32, 556>,
+    Allpass<f32, 441>,
+    Allpass<f32, 341>,
+    Allpass<f32, 225>,
+);
+
+struct Freeverb {
+    combs: Combs,
+    allpasses: Allpasses,
+    wet: f32,
+    dry: f32,
+}
+
+impl Freeverb {
+    fn new(room_size: f32, damping: f32, wet: f32) -> Self {
+        let feedback = 0.7 + 0.28 * room_size;
+        let damping = 0.4 * damping;
+        Freeverb {
+            combs: (
+                FeedbackComb::new(feedback, damping),
+                FeedbackComb::new(feedback, damping),
+                FeedbackComb::new(feedback, damping),
+                FeedbackComb::new(feedback, damping),
+                FeedbackComb::new(feedback, damping),
+                FeedbackComb::new(feedback, damping),
+                FeedbackComb::new(feedback, damping),
+                FeedbackComb::new(feedback, damping),
+            ),
+            allpasses: (
+                Allpass::new(0.5),
+                Allpass::new(0.5),
+                Allpass::new(0.5),
+                Allpass::new(0.5),
+            ),
+            wet,
+            dry: 1.0 - wet,
+        }
+    }
+}
+
+impl Filter for Freeverb {
+    type Sample = f32;
+
+    fn process(&mut self, sample: f32) -> f32 {
+        let input = sample * FIXED_GAIN;
+        let c = &mut self.combs;
+        let mut out = c.0.process(input)
+            + c.1.process(input)
+            + c.2.process(input)
+            + c.3.process(input)
+            + c.4.process(input)
+            + c.5.process(input)
+            + c.6.process(input)
+            + c.7.process(input);
+        let a = &mut self.allpasses;
+        out = a.0.process(out);
+        out = a.1.process(out);
+        out = a.2.process(out);
+        out = a.3.process(out);
+        out * self.wet + sample * self.dry
+    }
+
+    fn reset(&mut self) {
+        let c = &mut self.combs;
+        c.0.reset();
+        c.1.reset();
+        c.2.reset();
+        c.3.reset();
+        c.4.reset();
+        c.5.reset();
+        c.6.reset();
+        c.7.reset();
+        let a = &mut self.allpasses;
+        a.0.reset();
+        a.1.reset();
+        a.2.reset();
+        a.3.reset();
+    }
+}
+
+fn main() {
+    const SAMPLE_RATE: usize = 44_100;
+    let mut reverb = Freeverb::new(0.8, 0.5, 1.0);
+
+    // impulse response, energy per 100 ms
+    let mut block = [0.0f32; SAMPLE_RATE / 10];
+    block[0] = 1.0;
+    for i in 0..20 {
+        reverb.process_in_place(&mut block);
+        let energy: f32 = block.iter().map(|x| x * x).sum();
+        let db = 10.0 * energy.max(1e-30).log10();
+        println!("{:>5} ms {:>8.1} dB", i * 100, db);
+        block.fill(0.0);
+    }
+}
@@ -0,0 +1,274 @@
+use crate::sequence::SequenceNumber;
+use crate::timed::Timestamp;
+
+// Acknowledgement sent by the receiver: everything before cumulative has
+// arrived, and bit k of selective is set when cumulative + 1 + k has.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ack<S> {
+    pub cumulative: S,
+    pub selective: u64,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Pending<Ts, T> {
+    payload: T,
+    sent_at: Ts,
+    acked: bool,
+}
+
+// Selective repeat sender. Up to N packets are in flight; each is kept until
+// acknowledged and handed out again by poll_retransmit once it has gone
+// unacknowledged for the retransmission timeout. The window advances past
+// the acknowledged prefix.
+#[derive(Debug, Clone)]
+pub struct SendWindow<S, Ts, T, const N: usize>
+where
+    S: SequenceNumber,
+    Ts: Timestamp,
+    T: Copy,
+{
+    slots: [Option<Pending<Ts, T>>; N],
+    head: usize,
+    base: S,
+    in_flight: usize,
+    timeout: Ts,
+}
+
+impl<S, Ts, T, const N: usize> SendWindow<S, Ts, T, N>
+where
+    S: SequenceNumber,
+    Ts: Timestamp,
+    T: Copy,
+{
+    pub const fn new(first: S, timeout: Ts) -> Self {
+        SendWindow {
+            slots: [None; N],
+            head: 0,
+            base: first,
+            in_flight: 0,
+            timeout,
+        }
+    }
+
+    // Assigns the next sequence number to payload, which the caller then
+    // transmits. Gives the payload back when the window is full.
+    pub fn send(&mut self, payload: T, now: Ts) -> Result<S, T> {
+        if self.in_flight == N {
+            return Err(payload);
+        }
+        self.slots[(self.head + self.in_flight) % N] = Some(Pending {
+            payload,
+            sent_at: now,
+            acked: false,
+        });
+        let seq = self.base.wrapping_add(self.in_flight);
+        self.in_flight += 1;
+        Ok(seq)
+    }
+
+    // Returns true if seq was in flight and not acknowledged before.
+    pub fn ack(&mut self, seq: S) -> bool {
+        let newly = self
+            .offset(seq)
+            .and_then(|offset| self.slots[(self.head + offset) % N].as_mut())
+            .is_some_and(|pending| !core::mem::replace(&mut pending.acked, true));
+        self.advance();
+        newly
+    }
+
+    // Acknowledges everything before next_expected.
+    pub fn ack_cumulative(&mut self, next_expected: S) {
+        let count = self.base.distance_to(next_expected);
+        if count <= 0 {
+            return;
+        }
+        let count = usize::min(count as usize, self.in_flight);
+        for offset in 0..count {
+            if let Some(pending) = self.slots[(self.head + offset) % N].as_mut() {
+                pending.acked = true;
+            }
+        }
+        self.advance();
+    }
+
+    pub fn apply(&mut self, ack: Ack<S>) {
+        self.ack_cumulative(ack.cumulative);
+        let mut bits = ack.selective;
+        while bits != 0 {
+            let k = bits.trailing_zeros() as usize;
+            self.ack(ack.cumulative.wrapping_add(k + 1));
+            bits &= bits - 1;
+        }
+    }
+
+    // The oldest packet whose retransmission timeout has expired, restamped
+    // as sent now. Call repeatedly until None.
+    pub fn poll_retransmit(&mut self, now: Ts) -> Option<(S, T)> {
+        let timeout = self.timeout;
+        (0..self.in_flight).find_map(|offset| {
+            let pending = self.slots[(self.head + offset) % N].as_mut()?;
+            if pending.acked || now < pending.sent_at.saturating_add(timeout) {
+                return None;
+            }
+            pending.sent_at = now;
+            Some((self.base.wrapping_add(offset), pending.payload))
+        })
+    }
+
+    // when poll_retransmit will next have something, if anything is in flight
+    pub fn next_timeout(&self) -> Option<Ts> {
+        (0..self.in_flight)
+            .filter_map(|offset| self.slots[(self.head + offset) % N].as_ref())
+            .filter(|pending| !pending.acked)
+            .map(|pending| pending.sent_at.saturating_add(self.timeout))
+            .min()
+    }
+
+    // oldest unacknowledged sequence number, or the next one to be sent
+    pub fn base(&self) -> S {
+        self.base
+    }
+
+    pub fn next_seq(&self) -> S {
+        self.base.wrapping_add(self.in_flight)
+    }
+
+    pub fn in_flight(&self) -> usize {
+        self.in_flight
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.in_flight == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.in_flight == N
+    }
+
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    pub fn timeout(&self) -> Ts {
+        self.timeout
+    }
+
+    pub fn set_timeout(&mut self, timeout: Ts) {
+        self.timeout = timeout;
+    }
+
+    fn offset(&self, seq: S) -> Option<usize> {
+        let offset = self.base.distance_to(seq);
+        (0..self.in_flight as i64)
+            .contains(&offset)
+            .then_some(offset as usize)
+    }
+
+    fn advance(&mut self) {
+        while self.in_flight > 0 {
+            let slot = &mut self.slots[self.head];
+            if !slot.is_some_and(|pending| pending.acked) {
+                break;
+            }
+            *slot = None;
+            self.head = (self.head + 1) % N;
+            self.base = self.base.wrapping_add(1);
+            self.in_flight -= 1;
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Received {
+    New,
+    // already buffered or delivered; acknowledge again
+    Duplicate,
+    // N or more ahead of the next sequence number to deliver
+    OutOfWindow,
+}
+
+// Selective repeat receiver. Buffers packets within N of the next sequence
+// number to deliver and hands them out in order.
+#[derive(Debug, Clone)]
+pub struct ReceiveWindow<S, T, const N: usize>
+where
+    S: SequenceNumber,
+    T: Copy,
+{
+    slots: [Option<T>; N],
+    head: usize,
+    base: S,
+}
+
+impl<S, T, const N: usize> ReceiveWindow<S, T, N>
+where
+    S: SequenceNumber,
+    T: Copy,
+{
+    pub const fn new(first: S) -> Self {
+        ReceiveWindow {
+            slots: [None; N],
+            head: 0,
+            base: first,
+        }
+    }
+
+    pub fn receive(&mut self, seq: S, payload: T) -> Received {
+        let offset = self.base.distance_to(seq);
+        if offset < 0 {
+            return Received::Duplicate;
+        }
+        if offset as usize >= N {
+            return Received::OutOfWindow;
+        }
+        let slot = &mut self.slots[(self.head + offset as usize) % N];
+        if slot.is_some() {
+            return Received::Duplicate;
+        }
+        *slot = Some(payload);
+        Received::New
+    }
+
+    // the next packet in order, if it has arrived
+    pub fn pop(&mut self) -> Option<(S, T)> {
+        let payload = self.slots.get_mut(self.head)?.take()?;
+        let seq = self.base;
+        self.head = (self.head + 1) % N;
+        self.base = self.base.wrapping_add(1);
+        Some((seq, payload))
+    }
+
+    // Buffered packets count as received, whether or not they were popped.
+    pub fn ack(&self) -> Ack<S> {
+        let received = |offset: usize| offset < N && self.slots[(self.head + offset) % N].is_some();
+        let contiguous = (0..N).take_while(|offset| received(*offset)).count();
+        let selective = (0..64).fold(0, |bits, k| {
+            if received(contiguous + 1 + k) {
+                bits | 1 << k
+            } else {
+                bits
+            }
+        });
+        Ack {
+            cumulative: self.base.wrapping_add(contiguous),
+            selective,
+        }
+    }
+
+    // next sequence number to deliver
+    pub fn base(&self) -> S {
+        self.base
+    }
+
+    pub fn len(&self) -> usize {
+        self.slots.iter().flatten().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.iter().all(Option::is_none)
+    }
+
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+}
@@ -0,0 +1,231 @@
+use crate::sync::{AtomicUsize, Ordering};
+use core::cell::UnsafeCell;
+
+// Bipartite buffer: every committed record occupies one contiguous region.
+// Data lives in [read, write) or, once the writer wrapped around, in
+// [read, last) followed by [0, write). The writer never catches up to the
+// reader, so write < read always means the buffer is inverted.
+#[derive(Debug, Clone)]
+pub struct BipBuffer<const N: usize> {
+    buffer: [u8; N],
+    read: usize,
+    write: usize,
+    last: usize,
+    reserve_start: usize,
+    reserved: usize,
+}
+
+#[inline(always)]
+fn reserve_start(read: usize, write: usize, len: usize, capacity: usize) -> Option<usize> {
+    if write >= read {
+        if capacity - write >= len {
+            Some(write)
+        } else if len < read {
+            Some(0)
+        } else {
+            None
+        }
+    } else if write + len < read {
+        Some(write)
+    } else {
+        None
+    }
+}
+
+impl<const N: usize> BipBuffer<N> {
+    pub const fn new() -> Self {
+        BipBuffer {
+            buffer: [0; N],
+            read: 0,
+            write: 0,
+            last: 0,
+            reserve_start: 0,
+            reserved: 0,
+        }
+    }
+
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.read == self.write
+    }
+
+    pub fn reserve(&mut self, len: usize) -> Option<&mut [u8]> {
+        if self.read == self.write {
+            // nothing stored, start over to offer the largest region
+            self.read = 0;
+            self.write = 0;
+        }
+        let start = reserve_start(self.read, self.write, len, N)?;
+        self.reserve_start = start;
+        self.reserved = len;
+        Some(&mut self.buffer[start..start + len])
+    }
+
+    pub fn commit(&mut self, n: usize) {
+        assert!(n <= self.reserved, "commit exceeds reservation");
+        if n != 0 {
+            if self.reserve_start != self.write {
+                self.last = self.write;
+            }
+            self.write = self.reserve_start + n;
+        }
+        self.reserved = 0;
+    }
+
+    // contiguous committed bytes, oldest first
+    pub fn read(&self) -> &[u8] {
+        if self.write < self.read {
+            if self.read == self.last {
+                &self.buffer[..self.write]
+            } else {
+                &self.buffer[self.read..self.last]
+            }
+        } else {
+            &self.buffer[self.read..self.write]
+        }
+    }
+
+    pub fn release(&mut self, n: usize) {
+        if self.write < self.read && self.read == self.last {
+            self.read = 0;
+        }
+        let available = self.read().len();
+        assert!(n <= available, "release exceeds readable bytes");
+        self.read += n;
+        if self.write < self.read && self.read == self.last {
+            self.read = 0;
+        }
+    }
+}
+
+impl<const N: usize> Default for BipBuffer<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub struct SpscBipBuffer<const N: usize> {
+    // producer and consumer access disjoint sub-slices, which loom's cell
+    // can't express, so only the index protocol is model checked
+    buffer: UnsafeCell<[u8; N]>,
+    read: AtomicUsize,
+    write: AtomicUsize,
+    last: AtomicUsize,
+}
+
+unsafe impl<const N: usize> Sync for SpscBipBuffer<N> {}
+
+impl<const N: usize> SpscBipBuffer<N> {
+    pub fn new() -> Self {
+        SpscBipBuffer {
+            buffer: UnsafeCell::new([0; N]),
+            read: AtomicUsize::new(0),
+            write: AtomicUsize::new(0),
+            last: AtomicUsize::new(0),
+        }
+    }
+
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    pub fn split(&mut self) -> (BipProducer<'_, N>, BipConsumer<'_, N>) {
+        (
+            BipProducer {
+                bip: self,
+                reserve_start: 0,
+                reserved: 0,
+            },
+            BipConsumer { bip: self },
+        )
+    }
+
+    #[inline(always)]
+    fn region(&self, start: usize, len: usize) -> *mut u8 {
+        debug_assert!(start + len <= N);
+        // SAFETY: stays inside the array, callers only touch disjoint regions
+        unsafe { self.buffer.get().cast::<u8>().add(start) }
+    }
+}
+
+impl<const N: usize> Default for SpscBipBuffer<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub struct BipProducer<'a, const N: usize> {
+    bip: &'a SpscBipBuffer<N>,
+    reserve_start: usize,
+    reserved: usize,
+}
+
+impl<const N: usize> BipProducer<'_, N> {
+    pub fn reserve(&mut self, len: usize) -> Option<&mut [u8]> {
+        let write = self.bip.write.load(Ordering::Relaxed);
+        let read = self.bip.read.load(Ordering::Acquire);
+        let start = reserve_start(read, write, len, N)?;
+        self.reserve_start = start;
+        self.reserved = len;
+        // SAFETY: the consumer never reads outside of committed regions
+        Some(unsafe { core::slice::from_raw_parts_mut(self.bip.region(start, len), len) })
+    }
+
+    pub fn commit(&mut self, n: usize) {
+        assert!(n <= self.reserved, "commit exceeds reservation");
+        if n != 0 {
+            let write = self.bip.write.load(Ordering::Relaxed);
+            if self.reserve_start != write {
+                self.bip.last.store(write, Ordering::Release);
+            }
+            self.bip
+                .write
+                .store(self.reserve_start + n, Ordering::Release);
+        }
+        self.reserved = 0;
+    }
+}
+
+pub struct BipConsumer<'a, const N: usize> {
+    bip: &'a SpscBipBuffer<N>,
+}
+
+impl<const N: usize> BipConsumer<'_, N> {
+    // moves to the front once everything up to the wrap point was released
+    fn wrap(&self, read: usize, write: usize) -> usize {
+        if write < read && read == self.bip.last.load(Ordering::Acquire) {
+            self.bip.read.store(0, Ordering::Release);
+            0
+        } else {
+            read
+        }
+    }
+
+    fn readable(&self) -> (usize, usize) {
+        let write = self.bip.write.load(Ordering::Acquire);
+        let read = self.wrap(self.bip.read.load(Ordering::Relaxed), write);
+        let end = if write < read {
+            self.bip.last.load(Ordering::Acquire)
+        } else {
+            write
+        };
+        (read, end)
+    }
+
+    pub fn read(&mut self) -> &[u8] {
+        let (read, end) = self.readable();
+        // SAFETY: the producer never writes into committed regions
+        unsafe { core::slice::from_raw_parts(self.bip.region(read, end - read), end - read) }
+    }
+
+    pub fn release(&mut self, n: usize) {
+        let (read, end) = self.readable();
+        assert!(n <= end - read, "release exceeds readable bytes");
+        self.bip.read.store(read + n, Ordering::Release);
+        let write = self.bip.write.load(Ordering::Acquire);
+        self.wrap(read + n, write);
+    }
+}
@@ -0,0 +1,296 @@
+use crate::float::Float;
+use crate::RingBuffer;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Normalization {
+    Raw,
+    // divided by the window length
+    Biased,
+    // divided by the number of overlapping samples at each lag
+    Unbiased,
+    // divided by the geometric mean of both energies, in [-1, 1]
+    Coefficient,
+}
+
+impl Normalization {
+    fn apply<F: Float>(self, raw: F, len: usize, lag: usize, energy_x: F, energy_y: F) -> F {
+        match self {
+            Normalization::Raw => raw,
+            Normalization::Biased if len > 0 => raw / F::from_usize(len),
+            Normalization::Unbiased if len > lag => raw / F::from_usize(len - lag),
+            Normalization::Coefficient => {
+                let energy = (energy_x * energy_y).sqrt();
+                if energy > F::ZERO {
+                    raw / energy
+                } else {
+                    F::ZERO
+                }
+            }
+            _ => F::ZERO,
+        }
+    }
+}
+
+#[inline(always)]
+fn tail<'a, F>(halves: (&'a [F], &'a [F]), pos: usize) -> &'a [F] {
+    match pos.checked_sub(halves.0.len()) {
+        None => &halves.0[pos..],
+        Some(pos) => &halves.1[pos..],
+    }
+}
+
+// sum of x[x_start + i] * y[y_start + i] for i in 0..len, in logical order,
+// walking the contiguous halves of both rings chunk by chunk
+fn lagged_dot<F: Float>(
+    x: (&[F], &[F]),
+    x_start: usize,
+    y: (&[F], &[F]),
+    y_start: usize,
+    len: usize,
+) -> F {
+    let mut sum = F::ZERO;
+    let (mut xi, mut yi, mut left) = (x_start, y_start, len);
+    while left > 0 {
+        let (xs, ys) = (tail(x, xi), tail(y, yi));
+        let chunk = usize::min(left, usize::min(xs.len(), ys.len()));
+        for (a, b) in xs[..chunk].iter().zip(&ys[..chunk]) {
+            sum += *a * *b;
+        }
+        xi += chunk;
+        yi += chunk;
+        left -= chunk;
+    }
+    sum
+}
+
+// out[i] holds the correlation at lag first_lag + i: sum of x[n] * x[n - lag]
+pub fn autocorrelation<F, const N: usize>(
+    ring: &RingBuffer<F, N>,
+    first_lag: usize,
+    normalization: Normalization,
+    out: &mut [F],
+) where
+    F: Float,
+{
+    let halves = ring.as_slices();
+    let energy = lagged_dot(halves, 0, halves, 0, N);
+    for (i, r) in out.iter_mut().enumerate() {
+        let lag = first_lag + i;
+        let raw = if lag < N {
+            lagged_dot(halves, lag, halves, 0, N - lag)
+        } else {
+            F::ZERO
+        };
+        *r = normalization.apply(raw, N, lag, energy, energy);
+    }
+}
+
+// out[i] holds the correlation at lag first_lag + i: sum of x[n] * y[n + lag].
+// A positive peak lag means y trails x by that many samples.
+pub fn cross_correlation<F, const N: usize>(
+    x: &RingBuffer<F, N>,
+    y: &RingBuffer<F, N>,
+    first_lag: isize,
+    normalization: Normalization,
+    out: &mut [F],
+) where
+    F: Float,
+{
+    let (xs, ys) = (x.as_slices(), y.as_slices());
+    let energy_x = lagged_dot(xs, 0, xs, 0, N);
+    let energy_y = lagged_dot(ys, 0, ys, 0, N);
+    for (i, r) in out.iter_mut().enumerate() {
+        let lag = first_lag + i as isize;
+        let shift = lag.unsigned_abs();
+        let raw = match (shift < N, lag >= 0) {
+            (false, _) => F::ZERO,
+            (true, true) => lagged_dot(xs, 0, ys, shift, N - shift),
+            (true, false) => lagged_dot(xs, shift, ys, 0, N - shift),
+        };
+        *r = normalization.apply(raw, N, shift, energy_x, energy_y);
+    }
+}
+
+// Autocorrelation of the last N samples at lags 0..L, updated in O(L) per
+// sample from the sample entering and the one leaving the window.
+#[derive(Debug, Clone)]
+pub struct Autocorrelator<F, const N: usize, const L: usize>
+where
+    F: Float,
+{
+    history: RingBuffer<F, N>,
+    len: usize,
+    lags: [F; L],
+    since_refresh: usize,
+}
+
+impl<F, const N: usize, const L: usize> Autocorrelator<F, N, L>
+where
+    F: Float,
+{
+    pub const fn new() -> Self {
+        Autocorrelator {
+            history: RingBuffer::new(F::ZERO),
+            len: 0,
+            lags: [F::ZERO; L],
+            since_refresh: 0,
+        }
+    }
+
+    pub fn put(&mut self, x: F) {
+        if N == 0 {
+            return;
+        }
+        let old = self.history.replace(x);
+        if self.len == N {
+            for k in 0..usize::min(L, N) {
+                let partner = match k {
+                    0 => old,
+                    _ => self.history.get_oldest(k - 1),
+                };
+                self.lags[k] -= old * partner;
+            }
+        } else {
+            self.len += 1;
+        }
+        for k in 0..usize::min(L, self.len) {
+            self.lags[k] += x * self.history.get_newest(k);
+        }
+        self.since_refresh += 1;
+        if self.since_refresh == N {
+            self.since_refresh = 0;
+            // unused slots still hold zero and add nothing
+            autocorrelation(&self.history, 0, Normalization::Raw, &mut self.lags);
+        }
+    }
+
+    pub fn history(&self) -> &RingBuffer<F, N> {
+        &self.history
+    }
+
+    pub fn lag(&self, k: usize, normalization: Normalization) -> F {
+        let energy = self.lags.first().copied().unwrap_or(F::ZERO);
+        normalization.apply(self.lags[k], self.len, k, energy, energy)
+    }
+
+    pub fn correlation(&self, normalization: Normalization, out: &mut [F]) {
+        for (k, r) in out.iter_mut().enumerate().take(L) {
+            *r = self.lag(k, normalization);
+        }
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+impl<F, const N: usize, const L: usize> Default for Autocorrelator<F, N, L>
+where
+    F: Float,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Cross-correlation of the last N sample pairs at lags 0..L, where lag k
+// pairs x[n] with y[n + k]. Swap the inputs for negative lags.
+#[derive(Debug, Clone)]
+pub struct CrossCorrelator<F, const N: usize, const L: usize>
+where
+    F: Float,
+{
+    x: RingBuffer<F, N>,
+    y: RingBuffer<F, N>,
+    len: usize,
+    lags: [F; L],
+    energy_x: F,
+    energy_y: F,
+    since_refresh: usize,
+}
+
+impl<F, const N: usize, const L: usize> CrossCorrelator<F, N, L>
+where
+    F: Float,
+{
+    pub const fn new() -> Self {
+        CrossCorrelator {
+            x: RingBuffer::new(F::ZERO),
+            y: RingBuffer::new(F::ZERO),
+            len: 0,
+            lags: [F::ZERO; L],
+            energy_x: F::ZERO,
+            energy_y: F::ZERO,
+            since_refresh: 0,
+        }
+    }
+
+    pub fn put(&mut self, x: F, y: F) {
+        if N == 0 {
+            return;
+        }
+        let old_x = self.x.replace(x);
+        let old_y = self.y.replace(y);
+        if self.len == N {
+            for k in 0..usize::min(L, N) {
+                let partner = match k {
+                    0 => old_y,
+                    _ => self.y.get_oldest(k - 1),
+                };
+                self.lags[k] -= old_x * partner;
+            }
+            self.energy_x -= old_x * old_x;
+            self.energy_y -= old_y * old_y;
+        } else {
+            self.len += 1;
+        }
+        for k in 0..usize::min(L, self.len) {
+            self.lags[k] += self.x.get_newest(k) * y;
+        }
+        self.energy_x += x * x;
+        self.energy_y += y * y;
+        self.since_refresh += 1;
+        if self.since_refresh == N {
+            self.since_refresh = 0;
+            self.refresh();
+        }
+    }
+
+    fn refresh(&mut self) {
+        let (xs, ys) = (self.x.as_slices(), self.y.as_slices());
+        self.energy_x = lagged_dot(xs, 0, xs, 0, N);
+        self.energy_y = lagged_dot(ys, 0, ys, 0, N);
+        cross_correlation(&self.x, &self.y, 0, Normalization::Raw, &mut self.lags);
+    }
+
+    pub fn lag(&self, k: usize, normalization: Normalization) -> F {
+        normalization.apply(self.lags[k], self.len, k, self.energy_x, self.energy_y)
+    }
+
+    pub fn correlation(&self, normalization: Normalization, out: &mut [F]) {
+        for (k, r) in out.iter_mut().enumerate().take(L) {
+            *r = self.lag(k, normalization);
+        }
+    }
+
+    // lag with the largest correlation, the delay of y relative to x
+    pub fn peak_lag(&self) -> Option<usize> {
+        (0..usize::min(L, self.len)).fold(None, |best, k| match best {
+            Some(b) if self.lags[b] >= self.lags[k] => Some(b),
+            _ => Some(k),
+        })
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+impl<F, const N: usize, const L: usize> Default for CrossCorrelator<F, N, L>
+where
+    F: Float,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,262 @@
+use crate::float::Float;
+use crate::RingBuffer;
+use core::ops::{Add, Mul, Sub};
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Complex<F> {
+    pub re: F,
+    pub im: F,
+}
+
+impl<F> Complex<F>
+where
+    F: Float,
+{
+    pub const fn new(re: F, im: F) -> Self {
+        Complex { re, im }
+    }
+
+    pub fn from_polar(magnitude: F, phase: F) -> Self {
+        Complex::new(magnitude * phase.cos(), magnitude * phase.sin())
+    }
+
+    pub fn norm_sqr(self) -> F {
+        self.re * self.re + self.im * self.im
+    }
+
+    pub fn norm(self) -> F {
+        self.norm_sqr().sqrt()
+    }
+
+    pub fn arg(self) -> F {
+        self.im.atan2(self.re)
+    }
+
+    pub fn scale(self, k: F) -> Self {
+        Complex::new(self.re * k, self.im * k)
+    }
+}
+
+impl<F: Float> Add for Complex<F> {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self {
+        Complex::new(self.re + rhs.re, self.im + rhs.im)
+    }
+}
+
+impl<F: Float> Sub for Complex<F> {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        Complex::new(self.re - rhs.re, self.im - rhs.im)
+    }
+}
+
+impl<F: Float> Mul for Complex<F> {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self {
+        Complex::new(
+            self.re * rhs.re - self.im * rhs.im,
+            self.re * rhs.im + self.im * rhs.re,
+        )
+    }
+}
+
+#[inline(always)]
+fn angular<F: Float>(bin: F, len: usize) -> F {
+    F::from_f64(2.0 * core::f64::consts::PI) * bin / F::from_usize(len)
+}
+
+// nearest bin of an N point DFT for a frequency in the unit of sample_rate
+pub fn bin_for_frequency<F: Float>(frequency: F, sample_rate: F, len: usize) -> usize {
+    let bin = frequency * F::from_usize(len) / sample_rate;
+    (bin.to_f64() + 0.5) as usize
+}
+
+// Tracks selected bins of the DFT over the last N samples in O(1) per bin and
+// sample: X(n) = r e^(j2πk/N) (X(n-1) + x(n) - r^N x(n-N)). With r = 1 the
+// bins equal the exact DFT but rounding errors accumulate forever; r slightly
+// below 1 lets them decay at the cost of a slightly damped response.
+#[derive(Debug, Clone)]
+pub struct SlidingDft<F, const N: usize, const B: usize>
+where
+    F: Float,
+{
+    history: RingBuffer<F, N>,
+    bins: [usize; B],
+    twiddles: [Complex<F>; B],
+    damping: F,
+    damping_n: F,
+    values: [Complex<F>; B],
+}
+
+impl<F, const N: usize, const B: usize> SlidingDft<F, N, B>
+where
+    F: Float,
+{
+    pub fn new(bins: [usize; B]) -> Self {
+        Self::with_damping(bins, F::ONE)
+    }
+
+    pub fn with_damping(bins: [usize; B], damping: F) -> Self {
+        debug_assert!(damping > F::ZERO && damping <= F::ONE);
+        SlidingDft {
+            history: RingBuffer::new(F::ZERO),
+            bins,
+            twiddles: bins.map(|k| Complex::from_polar(damping, angular(F::from_usize(k), N))),
+            damping,
+            damping_n: damping.powf(F::from_usize(N)),
+            values: [Complex::default(); B],
+        }
+    }
+
+    pub fn put(&mut self, x: F) {
+        if N == 0 {
+            return;
+        }
+        let old = self.history.replace(x);
+        let delta = Complex::new(x - self.damping_n * old, F::ZERO);
+        for (value, twiddle) in self.values.iter_mut().zip(&self.twiddles) {
+            *value = *twiddle * (*value + delta);
+        }
+    }
+
+    pub fn put_slice(&mut self, items: &[F]) {
+        for &x in items {
+            self.put(x);
+        }
+    }
+
+    // recomputes every bin from the history in O(N) each, discarding
+    // accumulated rounding errors
+    pub fn recompute(&mut self) {
+        for (value, twiddle) in self.values.iter_mut().zip(&self.twiddles) {
+            let mut power = *twiddle;
+            let mut sum = Complex::default();
+            for x in self.history.iter().rev() {
+                sum = sum + power.scale(x);
+                power = power * *twiddle;
+            }
+            *value = sum;
+        }
+    }
+
+    pub fn bins(&self) -> &[usize; B] {
+        &self.bins
+    }
+
+    pub fn damping(&self) -> F {
+        self.damping
+    }
+
+    pub fn bin(&self, i: usize) -> Complex<F> {
+        self.values[i]
+    }
+
+    pub fn magnitude(&self, i: usize) -> F {
+        self.values[i].norm()
+    }
+
+    pub fn power(&self, i: usize) -> F {
+        self.values[i].norm_sqr()
+    }
+
+    pub fn phase(&self, i: usize) -> F {
+        self.values[i].arg()
+    }
+
+    pub fn history(&self) -> &RingBuffer<F, N> {
+        &self.history
+    }
+
+    pub fn reset(&mut self) {
+        self.history = RingBuffer::new(F::ZERO);
+        self.values = [Complex::default(); B];
+    }
+}
+
+// Goertzel filter evaluating a single, possibly fractional, DFT bin over
+// consecutive blocks of len samples.
+#[derive(Debug, Clone, Copy)]
+pub struct Goertzel<F> {
+    coeff: F,
+    omega: F,
+    len: usize,
+    count: usize,
+    s1: F,
+    s2: F,
+    last: Option<Complex<F>>,
+}
+
+impl<F> Goertzel<F>
+where
+    F: Float,
+{
+    pub fn new(bin: F, len: usize) -> Self {
+        let omega = angular(bin, len);
+        Goertzel {
+            coeff: F::from_f64(2.0) * omega.cos(),
+            omega,
+            len,
+            count: 0,
+            s1: F::ZERO,
+            s2: F::ZERO,
+            last: None,
+        }
+    }
+
+    pub fn from_frequency(frequency: F, sample_rate: F, len: usize) -> Self {
+        Self::new(frequency * F::from_usize(len) / sample_rate, len)
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // returns the bin once a block is complete and starts the next one
+    pub fn put(&mut self, x: F) -> Option<Complex<F>> {
+        if self.len == 0 {
+            return None;
+        }
+        let s = x + self.coeff * self.s1 - self.s2;
+        self.s2 = self.s1;
+        self.s1 = s;
+        self.count += 1;
+        if self.count < self.len {
+            return None;
+        }
+        let (sin, cos) = (self.omega.sin(), self.omega.cos());
+        let y = Complex::new(self.s1 - self.s2 * cos, self.s2 * sin);
+        // y carries a phase offset of omega * (len - 1) relative to the DFT
+        let phase = -self.omega * F::from_usize(self.len - 1);
+        let value = y * Complex::from_polar(F::ONE, phase);
+        self.last = Some(value);
+        self.count = 0;
+        self.s1 = F::ZERO;
+        self.s2 = F::ZERO;
+        Some(value)
+    }
+
+    // result of the last complete block
+    pub fn last(&self) -> Option<Complex<F>> {
+        self.last
+    }
+
+    pub fn magnitude(&self) -> Option<F> {
+        self.last.map(Complex::norm)
+    }
+
+    pub fn phase(&self) -> Option<F> {
+        self.last.map(Complex::arg)
+    }
+
+    pub fn reset(&mut self) {
+        self.count = 0;
+        self.s1 = F::ZERO;
+        self.s2 = F::ZERO;
+        self.last = None;
+    }
+}
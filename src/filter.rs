@@ -0,0 +1,307 @@
+use crate::float::Float;
+use crate::RingBuffer;
+
+pub trait Filter {
+    type Sample: Copy;
+
+    fn process(&mut self, sample: Self::Sample) -> Self::Sample;
+
+    fn reset(&mut self);
+
+    // processes min(input.len(), output.len()) samples
+    fn process_block(&mut self, input: &[Self::Sample], output: &mut [Self::Sample]) {
+        for (x, y) in input.iter().zip(output.iter_mut()) {
+            *y = self.process(*x);
+        }
+    }
+
+    fn process_in_place(&mut self, block: &mut [Self::Sample]) {
+        for x in block.iter_mut() {
+            *x = self.process(*x);
+        }
+    }
+}
+
+// Running sums over a window drift when updated incrementally, so they are
+// recomputed from the ring once every N samples.
+#[derive(Debug, Clone)]
+pub struct MovingAverage<F, const N: usize>
+where
+    F: Float,
+{
+    history: RingBuffer<F, N>,
+    count: usize,
+    sum: F,
+    since_refresh: usize,
+}
+
+impl<F, const N: usize> MovingAverage<F, N>
+where
+    F: Float,
+{
+    pub const fn new() -> Self {
+        MovingAverage {
+            history: RingBuffer::new(F::ZERO),
+            count: 0,
+            sum: F::ZERO,
+            since_refresh: 0,
+        }
+    }
+
+    pub fn average(&self) -> F {
+        if self.count == 0 {
+            F::ZERO
+        } else {
+            self.sum / F::from_usize(self.count)
+        }
+    }
+}
+
+impl<F, const N: usize> Default for MovingAverage<F, N>
+where
+    F: Float,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F, const N: usize> Filter for MovingAverage<F, N>
+where
+    F: Float,
+{
+    type Sample = F;
+
+    fn process(&mut self, sample: F) -> F {
+        if N == 0 {
+            return sample;
+        }
+        let old = self.history.replace(sample);
+        self.count = usize::min(self.count + 1, N);
+        self.since_refresh += 1;
+        if self.since_refresh == N {
+            self.since_refresh = 0;
+            self.sum = self.history.iter().fold(F::ZERO, |acc, x| acc + x);
+        } else {
+            self.sum += sample - old;
+        }
+        self.average()
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+// Weights fall linearly from N for the newest to 1 for the oldest sample.
+#[derive(Debug, Clone)]
+pub struct WeightedMovingAverage<F, const N: usize>
+where
+    F: Float,
+{
+    history: RingBuffer<F, N>,
+    count: usize,
+    sum: F,
+    weighted_sum: F,
+    since_refresh: usize,
+}
+
+impl<F, const N: usize> WeightedMovingAverage<F, N>
+where
+    F: Float,
+{
+    pub const fn new() -> Self {
+        WeightedMovingAverage {
+            history: RingBuffer::new(F::ZERO),
+            count: 0,
+            sum: F::ZERO,
+            weighted_sum: F::ZERO,
+            since_refresh: 0,
+        }
+    }
+
+    pub fn average(&self) -> F {
+        if self.count == 0 {
+            return F::ZERO;
+        }
+        let weights = F::from_usize(self.count * (self.count + 1) / 2);
+        self.weighted_sum / weights
+    }
+
+    fn refresh(&mut self) {
+        self.sum = F::ZERO;
+        self.weighted_sum = F::ZERO;
+        for k in 0..self.count {
+            let x = self.history.get_newest(k);
+            self.sum += x;
+            self.weighted_sum += F::from_usize(self.count - k) * x;
+        }
+    }
+}
+
+impl<F, const N: usize> Default for WeightedMovingAverage<F, N>
+where
+    F: Float,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F, const N: usize> Filter for WeightedMovingAverage<F, N>
+where
+    F: Float,
+{
+    type Sample = F;
+
+    fn process(&mut self, sample: F) -> F {
+        if N == 0 {
+            return sample;
+        }
+        let old = self.history.replace(sample);
+        if self.count < N {
+            self.count += 1;
+            self.weighted_sum += F::from_usize(self.count) * sample;
+            self.sum += sample;
+        } else {
+            // every older sample loses one unit of weight, the oldest drops out
+            self.weighted_sum += F::from_usize(N) * sample - self.sum;
+            self.sum += sample - old;
+        }
+        self.since_refresh += 1;
+        if self.since_refresh == N {
+            self.since_refresh = 0;
+            self.refresh();
+        }
+        self.average()
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+// Exponentially weighted moving average, seeded with the first sample.
+#[derive(Debug, Clone, Copy)]
+pub struct Ewma<F> {
+    alpha: F,
+    state: Option<F>,
+}
+
+impl<F> Ewma<F>
+where
+    F: Float,
+{
+    // alpha in (0, 1], larger values follow the input faster
+    pub fn new(alpha: F) -> Self {
+        debug_assert!(alpha > F::ZERO && alpha <= F::ONE);
+        Ewma { alpha, state: None }
+    }
+
+    // alpha = 2 / (N + 1), the usual match for an N sample moving average
+    pub fn with_span(span: usize) -> Self {
+        Self::new(F::from_f64(2.0) / F::from_usize(span + 1))
+    }
+
+    pub fn alpha(&self) -> F {
+        self.alpha
+    }
+
+    pub fn value(&self) -> Option<F> {
+        self.state
+    }
+}
+
+impl<F> Filter for Ewma<F>
+where
+    F: Float,
+{
+    type Sample = F;
+
+    fn process(&mut self, sample: F) -> F {
+        let next = match self.state {
+            Some(state) => state + self.alpha * (sample - state),
+            None => sample,
+        };
+        self.state = Some(next);
+        next
+    }
+
+    fn reset(&mut self) {
+        self.state = None;
+    }
+}
+
+// Holt's double exponential smoothing, tracking a level and a trend.
+#[derive(Debug, Clone, Copy)]
+pub struct Holt<F> {
+    alpha: F,
+    beta: F,
+    level: F,
+    trend: F,
+    seen: u8,
+}
+
+impl<F> Holt<F>
+where
+    F: Float,
+{
+    // alpha smooths the level, beta the trend, both in (0, 1]
+    pub fn new(alpha: F, beta: F) -> Self {
+        debug_assert!(alpha > F::ZERO && alpha <= F::ONE);
+        debug_assert!(beta > F::ZERO && beta <= F::ONE);
+        Holt {
+            alpha,
+            beta,
+            level: F::ZERO,
+            trend: F::ZERO,
+            seen: 0,
+        }
+    }
+
+    pub fn level(&self) -> F {
+        self.level
+    }
+
+    pub fn trend(&self) -> F {
+        self.trend
+    }
+
+    // extrapolates the current level k samples ahead
+    pub fn forecast(&self, k: usize) -> F {
+        self.level + F::from_usize(k) * self.trend
+    }
+}
+
+impl<F> Filter for Holt<F>
+where
+    F: Float,
+{
+    type Sample = F;
+
+    fn process(&mut self, sample: F) -> F {
+        match self.seen {
+            0 => {
+                self.level = sample;
+                self.seen = 1;
+            }
+            1 => {
+                self.trend = sample - self.level;
+                self.level = sample;
+                self.seen = 2;
+            }
+            _ => {
+                let last = self.level;
+                self.level = self.alpha * sample + (F::ONE - self.alpha) * (last + self.trend);
+                self.trend = self.beta * (self.level - last) + (F::ONE - self.beta) * self.trend;
+            }
+        }
+        self.level
+    }
+
+    fn reset(&mut self) {
+        self.level = F::ZERO;
+        self.trend = F::ZERO;
+        self.seen = 0;
+    }
+}
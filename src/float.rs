@@ -0,0 +1,85 @@
+use core::fmt::Debug;
+use core::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+// The subset of floating point arithmetic the signal processing types need,
+// implemented for f32 and f64.
+pub trait Float:
+    Copy
+    + Debug
+    + Default
+    + PartialOrd
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Neg<Output = Self>
+    + AddAssign
+    + SubAssign
+    + MulAssign
+    + DivAssign
+{
+    const ZERO: Self;
+    const ONE: Self;
+
+    fn from_f64(x: f64) -> Self;
+    fn to_f64(self) -> f64;
+    fn from_usize(n: usize) -> Self;
+    fn abs(self) -> Self;
+    fn sqrt(self) -> Self;
+    fn sin(self) -> Self;
+    fn cos(self) -> Self;
+    fn exp(self) -> Self;
+    fn log10(self) -> Self;
+    fn atan2(self, other: Self) -> Self;
+    fn powf(self, n: Self) -> Self;
+}
+
+macro_rules! impl_float {
+    ( $( $t:ty { $( $unary:ident: $unary_libm:ident ),* } { $( $binary:ident: $binary_libm:ident ),* } ),* ) => {
+        $(
+            impl Float for $t {
+                const ZERO: Self = 0.0;
+                const ONE: Self = 1.0;
+
+                #[inline(always)]
+                fn from_f64(x: f64) -> Self {
+                    x as $t
+                }
+
+                #[inline(always)]
+                fn to_f64(self) -> f64 {
+                    self as f64
+                }
+
+                #[inline(always)]
+                fn from_usize(n: usize) -> Self {
+                    n as $t
+                }
+
+                #[inline(always)]
+                fn abs(self) -> Self {
+                    <$t>::abs(self)
+                }
+
+                $(
+                    #[inline(always)]
+                    fn $unary(self) -> Self {
+                        libm::$unary_libm(self)
+                    }
+                )*
+
+                $(
+                    #[inline(always)]
+                    fn $binary(self, other: Self) -> Self {
+                        libm::$binary_libm(self, other)
+                    }
+                )*
+            }
+        )*
+    };
+}
+
+impl_float!(
+    f32 { sqrt: sqrtf, sin: sinf, cos: cosf, exp: expf, log10: log10f } { atan2: atan2f, powf: powf },
+    f64 { sqrt: sqrt, sin: sin, cos: cos, exp: exp, log10: log10 } { atan2: atan2, powf: pow }
+);
@@ -0,0 +1,181 @@
+use crate::iterators::iter::RingBufferIter;
+use crate::RingBuffer;
+use core::iter::FusedIterator;
+
+// Ring of N frames holding one sample for each of CH channels.
+#[derive(Debug, Clone)]
+pub struct FrameRing<T, const CH: usize, const N: usize>
+where
+    T: Copy,
+{
+    frames: RingBuffer<[T; CH], N>,
+}
+
+impl<T, const CH: usize, const N: usize> Default for FrameRing<T, CH, N>
+where
+    T: Default + Copy,
+{
+    fn default() -> Self {
+        FrameRing {
+            frames: RingBuffer::new([T::default(); CH]),
+        }
+    }
+}
+
+impl<T, const CH: usize, const N: usize> FrameRing<T, CH, N>
+where
+    T: Copy,
+{
+    pub const fn new(init_value: T) -> Self {
+        FrameRing {
+            frames: RingBuffer::new([init_value; CH]),
+        }
+    }
+
+    pub const fn channels(&self) -> usize {
+        CH
+    }
+
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    pub fn put_frame(&mut self, frame: [T; CH]) {
+        self.frames.put(frame);
+    }
+
+    // block length has to be a multiple of CH
+    pub fn put_interleaved(&mut self, block: &[T]) {
+        assert_eq!(block.len() % CH, 0, "block is not frame aligned");
+        for frame in block.chunks_exact(CH) {
+            self.frames.put(core::array::from_fn(|ch| frame[ch]));
+        }
+    }
+
+    // all channels need the same length
+    pub fn put_planar(&mut self, block: [&[T]; CH]) {
+        let len = block.first().map_or(0, |c| c.len());
+        assert!(
+            block.iter().all(|c| c.len() == len),
+            "channel lengths differ"
+        );
+        for frame in (0..len).map(|i| core::array::from_fn(|ch| block[ch][i])) {
+            self.frames.put(frame);
+        }
+    }
+
+    // fills out with the newest out.len() / CH frames, oldest first
+    pub fn read_interleaved(&self, out: &mut [T]) {
+        assert_eq!(out.len() % CH, 0, "block is not frame aligned");
+        let len = out.len() / CH;
+        assert!(len <= N, "more frames requested than stored");
+        for (i, frame) in out.chunks_exact_mut(CH).enumerate() {
+            frame.copy_from_slice(&self.frames.get_newest(len - 1 - i));
+        }
+    }
+
+    // fills every channel with its newest samples, oldest first
+    pub fn read_planar(&self, mut out: [&mut [T]; CH]) {
+        let len = out.first().map_or(0, |c| c.len());
+        assert!(out.iter().all(|c| c.len() == len), "channel lengths differ");
+        assert!(len <= N, "more frames requested than stored");
+        for i in 0..len {
+            let frame = self.frames.get_newest(len - 1 - i);
+            for (channel, sample) in out.iter_mut().zip(frame) {
+                channel[i] = sample;
+            }
+        }
+    }
+
+    pub fn get_oldest_frame(&self, idx: usize) -> [T; CH] {
+        self.frames.get_oldest(idx)
+    }
+
+    pub fn get_newest_frame(&self, idx: usize) -> [T; CH] {
+        self.frames.get_newest(idx)
+    }
+
+    pub fn get_oldest(&self, ch: usize, idx: usize) -> T {
+        self.frames.get_oldest(idx)[ch]
+    }
+
+    pub fn get_newest(&self, ch: usize, idx: usize) -> T {
+        self.frames.get_newest(idx)[ch]
+    }
+
+    pub fn frames(&self) -> RingBufferIter<'_, [T; CH], N> {
+        self.frames.iter()
+    }
+
+    pub fn iter_channel(&self, ch: usize) -> ChannelIter<'_, T, CH, N> {
+        assert!(ch < CH, "channel out of range");
+        ChannelIter {
+            ring: self,
+            channel: ch,
+            index_forward: 0,
+            index_backward: N,
+        }
+    }
+}
+
+pub struct ChannelIter<'a, T, const CH: usize, const N: usize>
+where
+    T: Copy,
+{
+    ring: &'a FrameRing<T, CH, N>,
+    channel: usize,
+    index_forward: usize,
+    index_backward: usize,
+}
+
+impl<T, const CH: usize, const N: usize> Iterator for ChannelIter<'_, T, CH, N>
+where
+    T: Copy,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index_forward >= self.index_backward {
+            return None;
+        }
+        let result = self.ring.get_oldest(self.channel, self.index_forward);
+        self.index_forward += 1;
+        Some(result)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.index_backward.saturating_sub(self.index_forward);
+        (len, Some(len))
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.index_forward += n;
+        self.next()
+    }
+}
+
+impl<T, const CH: usize, const N: usize> DoubleEndedIterator for ChannelIter<'_, T, CH, N>
+where
+    T: Copy,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.index_backward <= self.index_forward {
+            return None;
+        }
+        self.index_backward -= 1;
+        let result = self.ring.get_oldest(self.channel, self.index_backward);
+        Some(result)
+    }
+
+    fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+        self.index_backward = self.index_backward.saturating_sub(n);
+        self.next_back()
+    }
+}
+
+impl<T, const CH: usize, const N: usize> FusedIterator for ChannelIter<'_, T, CH, N> where T: Copy {}
+
+impl<T, const CH: usize, const N: usize> ExactSizeIterator for ChannelIter<'_, T, CH, N> where
+    T: Copy
+{
+}
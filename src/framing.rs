@@ -0,0 +1,187 @@
+use crate::float::Float;
+use crate::RingBuffer;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowKind {
+    Rectangular,
+    Hann,
+    Hamming,
+    Blackman,
+}
+
+impl WindowKind {
+    // periodic (DFT-even) coefficients, as used for spectral analysis
+    pub fn coefficients<F: Float, const N: usize>(self) -> [F; N] {
+        let step = 2.0 * core::f64::consts::PI / N as f64;
+        core::array::from_fn(|n| {
+            let phase = step * n as f64;
+            let w = match self {
+                WindowKind::Rectangular => 1.0,
+                WindowKind::Hann => 0.5 - 0.5 * libm::cos(phase),
+                WindowKind::Hamming => 0.54 - 0.46 * libm::cos(phase),
+                WindowKind::Blackman => {
+                    0.42 - 0.5 * libm::cos(phase) + 0.08 * libm::cos(2.0 * phase)
+                }
+            };
+            F::from_f64(w)
+        })
+    }
+}
+
+// Cuts a stream into windowed frames of N samples, one every hop samples,
+// starting as soon as the first N samples have arrived.
+#[derive(Debug, Clone)]
+pub struct Framer<F, const N: usize>
+where
+    F: Float,
+{
+    history: RingBuffer<F, N>,
+    window: [F; N],
+    frame: [F; N],
+    hop: usize,
+    // samples until the next frame is due
+    countdown: usize,
+}
+
+impl<F, const N: usize> Framer<F, N>
+where
+    F: Float,
+{
+    pub fn new(hop: usize, kind: WindowKind) -> Self {
+        Self::with_window(hop, kind.coefficients())
+    }
+
+    pub fn with_window(hop: usize, window: [F; N]) -> Self {
+        assert!(hop > 0 && hop <= N, "hop must be in 1..=N");
+        Framer {
+            history: RingBuffer::new(F::ZERO),
+            window,
+            frame: [F::ZERO; N],
+            hop,
+            countdown: N,
+        }
+    }
+
+    pub fn hop(&self) -> usize {
+        self.hop
+    }
+
+    pub fn window(&self) -> &[F; N] {
+        &self.window
+    }
+
+    pub fn put(&mut self, x: F) -> Option<&[F; N]> {
+        self.history.put(x);
+        self.countdown -= 1;
+        if self.countdown != 0 {
+            return None;
+        }
+        self.countdown = self.hop;
+        let (older, newer) = self.history.as_slices();
+        let samples = older.iter().chain(newer);
+        for ((y, x), w) in self.frame.iter_mut().zip(samples).zip(&self.window) {
+            *y = *x * *w;
+        }
+        Some(&self.frame)
+    }
+
+    // feeds a block of any size, calling on_frame for every completed frame
+    pub fn process<C>(&mut self, block: &[F], mut on_frame: C)
+    where
+        C: FnMut(&[F; N]),
+    {
+        for &x in block {
+            if let Some(frame) = self.put(x) {
+                on_frame(frame);
+            }
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.history = RingBuffer::new(F::ZERO);
+        self.countdown = N;
+    }
+}
+
+// Resynthesis counterpart of Framer: sums overlapping frames and releases
+// hop finished samples per frame, N samples after they entered the Framer.
+#[derive(Debug, Clone)]
+pub struct OverlapAdd<F, const N: usize>
+where
+    F: Float,
+{
+    accumulator: RingBuffer<F, N>,
+    window: Option<[F; N]>,
+    output: [F; N],
+    hop: usize,
+    gain: F,
+}
+
+impl<F, const N: usize> OverlapAdd<F, N>
+where
+    F: Float,
+{
+    pub fn new(hop: usize) -> Self {
+        assert!(hop > 0 && hop <= N, "hop must be in 1..=N");
+        OverlapAdd {
+            accumulator: RingBuffer::new(F::ZERO),
+            window: None,
+            output: [F::ZERO; N],
+            hop,
+            gain: F::ONE,
+        }
+    }
+
+    // applies a synthesis window to every frame and picks the gain that makes
+    // analysis and synthesis windows together sum to one
+    pub fn with_windows(hop: usize, analysis: &[F; N], synthesis: [F; N]) -> Self {
+        let mut ola = Self::new(hop);
+        ola.gain = F::ONE / cola_sum(analysis, Some(&synthesis), hop);
+        ola.window = Some(synthesis);
+        ola
+    }
+
+    pub fn gain(&self) -> F {
+        self.gain
+    }
+
+    pub fn set_gain(&mut self, gain: F) {
+        self.gain = gain;
+    }
+
+    pub fn add(&mut self, frame: &[F; N]) -> &[F] {
+        // borrow once, map_or on the Option itself would copy the window per sample
+        let window = self.window.as_ref();
+        for (i, (acc, x)) in self.accumulator.iter_mut().zip(frame).enumerate() {
+            let w = window.map_or(F::ONE, |w| w[i]);
+            *acc += *x * w;
+        }
+        for i in 0..self.hop {
+            self.output[i] = self.accumulator.get_oldest(0) * self.gain;
+            self.accumulator.put(F::ZERO);
+        }
+        &self.output[..self.hop]
+    }
+
+    pub fn reset(&mut self) {
+        self.accumulator = RingBuffer::new(F::ZERO);
+    }
+}
+
+// Sum of analysis[n] * synthesis[n] over all frames overlapping a sample, on
+// average. Constant overlap-add windows reconstruct exactly after dividing by it.
+pub fn cola_sum<F: Float, const N: usize>(
+    analysis: &[F; N],
+    synthesis: Option<&[F; N]>,
+    hop: usize,
+) -> F {
+    let total = (0..N).fold(F::ZERO, |acc, n| {
+        acc + analysis[n] * synthesis.map_or(F::ONE, |s| s[n])
+    });
+    total / F::from_usize(hop)
+}
+
+// The samples of a circularly convolved frame that overlap-save keeps.
+pub fn overlap_save_valid<F, const N: usize>(frame: &[F; N], hop: usize) -> &[F] {
+    &frame[N - hop..]
+}
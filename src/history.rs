@@ -0,0 +1,217 @@
+use alloc::borrow::ToOwned;
+use alloc::string::String;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Duplicates {
+    #[default]
+    Keep,
+    // skip a line equal to the newest entry
+    IgnoreConsecutive,
+    // remove older entries equal to a new line
+    EraseOlder,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Policy {
+    pub duplicates: Duplicates,
+    // skip lines starting with a space
+    pub ignore_space: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Match {
+    Prefix,
+    Substring,
+}
+
+impl Match {
+    fn matches(self, entry: &str, query: &str) -> bool {
+        match self {
+            Match::Prefix => entry.starts_with(query),
+            Match::Substring => entry.contains(query),
+        }
+    }
+}
+
+// Shell style history of the last N lines. Entries are addressed by age,
+// 0 being the newest. Navigation and search share a cursor that starts past
+// the newest entry and is reset by add and reset_navigation; the line being
+// edited when navigation starts is kept and returned when moving past the
+// newest entry again.
+#[derive(Debug, Clone)]
+pub struct History<const N: usize> {
+    entries: [Option<String>; N],
+    // next slot to write
+    head: usize,
+    len: usize,
+    policy: Policy,
+    cursor: Option<usize>,
+    draft: String,
+}
+
+impl<const N: usize> History<N> {
+    pub const fn new() -> Self {
+        Self::with_policy(Policy {
+            duplicates: Duplicates::Keep,
+            ignore_space: false,
+        })
+    }
+
+    pub const fn with_policy(policy: Policy) -> Self {
+        History {
+            entries: [const { None }; N],
+            head: 0,
+            len: 0,
+            policy,
+            cursor: None,
+            draft: String::new(),
+        }
+    }
+
+    // Returns whether line was recorded. Blank lines never are.
+    pub fn add(&mut self, line: &str) -> bool {
+        self.reset_navigation();
+        if N == 0 || line.trim().is_empty() {
+            return false;
+        }
+        if self.policy.ignore_space && line.starts_with(' ') {
+            return false;
+        }
+        match self.policy.duplicates {
+            Duplicates::Keep => {}
+            Duplicates::IgnoreConsecutive => {
+                if self.get(0) == Some(line) {
+                    return false;
+                }
+            }
+            Duplicates::EraseOlder => {
+                while let Some(age) = (0..self.len).find(|age| self.get(*age) == Some(line)) {
+                    self.remove(age);
+                }
+            }
+        }
+        self.entries[self.head] = Some(line.to_owned());
+        self.head = (self.head + 1) % N;
+        self.len = usize::min(self.len + 1, N);
+        true
+    }
+
+    pub fn get(&self, age: usize) -> Option<&str> {
+        if age >= self.len {
+            return None;
+        }
+        self.entries[self.slot(age)].as_deref()
+    }
+
+    // oldest to newest
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &str> + '_ {
+        (0..self.len).rev().filter_map(|age| self.get(age))
+    }
+
+    // Moves to the next older entry. input is the line being edited, kept
+    // when navigation starts.
+    pub fn prev(&mut self, input: &str) -> Option<&str> {
+        let age = self.cursor.map_or(0, |age| age + 1);
+        if age >= self.len {
+            return None;
+        }
+        self.move_to(age, input);
+        self.get(age)
+    }
+
+    // Moves to the next newer entry, or back to the edited line.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Option<&str> {
+        match self.cursor? {
+            0 => {
+                self.cursor = None;
+                Some(&self.draft)
+            }
+            age => {
+                self.cursor = Some(age - 1);
+                self.get(age - 1)
+            }
+        }
+    }
+
+    // Finds the next older matching entry, skipping ones equal to the entry
+    // under the cursor, and moves the cursor there.
+    pub fn search_backward(&mut self, query: &str, kind: Match, input: &str) -> Option<&str> {
+        let start = self.cursor.map_or(0, |age| age + 1);
+        let age = (start..self.len).find(|age| self.is_match(*age, query, kind))?;
+        self.move_to(age, input);
+        self.get(age)
+    }
+
+    pub fn search_forward(&mut self, query: &str, kind: Match) -> Option<&str> {
+        let cursor = self.cursor?;
+        let age = (0..cursor)
+            .rev()
+            .find(|age| self.is_match(*age, query, kind))?;
+        self.cursor = Some(age);
+        self.get(age)
+    }
+
+    // age of the entry under the cursor, None while editing a new line
+    pub fn cursor(&self) -> Option<usize> {
+        self.cursor
+    }
+
+    pub fn reset_navigation(&mut self) {
+        self.cursor = None;
+        self.draft.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    pub fn policy(&self) -> Policy {
+        self.policy
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::with_policy(self.policy);
+    }
+
+    fn slot(&self, age: usize) -> usize {
+        (self.head + N - 1 - age) % N
+    }
+
+    fn move_to(&mut self, age: usize, input: &str) {
+        if self.cursor.is_none() {
+            input.clone_into(&mut self.draft);
+        }
+        self.cursor = Some(age);
+    }
+
+    fn is_match(&self, age: usize, query: &str, kind: Match) -> bool {
+        let current = self.cursor.and_then(|cursor| self.get(cursor));
+        self.get(age)
+            .is_some_and(|entry| kind.matches(entry, query) && Some(entry) != current)
+    }
+
+    // shifts the newer entries down over the one at age
+    fn remove(&mut self, age: usize) {
+        for older in (1..=age).rev() {
+            self.entries[self.slot(older)] = self.entries[self.slot(older - 1)].take();
+        }
+        self.entries[self.slot(0)] = None;
+        self.head = (self.head + N - 1) % N;
+        self.len -= 1;
+    }
+}
+
+impl<const N: usize> Default for History<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
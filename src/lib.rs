@@ -1,7 +1,43 @@
 #![no_std]
 
+pub mod arq;
+pub mod bip;
+pub mod correlation;
+pub mod dft;
+pub mod filter;
+pub mod float;
+pub mod frame_ring;
+pub mod framing;
+#[cfg(feature = "alloc")]
+pub mod history;
 pub mod iterators;
+pub mod meter;
+#[cfg(all(feature = "mirrored", target_os = "linux"))]
+pub mod mirrored;
+pub mod mpmc;
+#[cfg(all(feature = "persistent", unix))]
+pub mod persistent;
+pub mod pyramid;
+pub mod rate_limit;
+pub mod recorder;
+pub mod regression;
+pub mod reorder;
+pub mod replay;
+pub mod resample;
+pub mod reverb;
+pub mod scrollback;
+pub mod seqlock;
+pub mod sequence;
+mod sync;
 mod test;
+pub mod timed;
+pub mod trigger;
+pub mod undo;
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(any(test, feature = "std"))]
+extern crate std;
 
 #[derive(Debug, Clone)]
 pub struct RingBuffer<T, const N: usize> {
@@ -55,6 +91,20 @@ where
         self.head = Self::wrap_idx(self.head + 1);
     }
 
+    pub fn put_slice(&mut self, items: &[T]) {
+        if items.is_empty() || N == 0 {
+            return;
+        }
+        // only the last N items survive anyway
+        let skip = items.len().saturating_sub(N);
+        self.head = Self::wrap_idx(self.head + skip % N);
+        let items = &items[skip..];
+        let (first, second) = items.split_at(usize::min(items.len(), N - self.head));
+        self.buffer[self.head..self.head + first.len()].copy_from_slice(first);
+        self.buffer[..second.len()].copy_from_slice(second);
+        self.head = Self::wrap_idx(self.head + items.len());
+    }
+
     pub fn replace(&mut self, item: T) -> T {
         let old = self.buffer[self.head];
         self.buffer[self.head] = item;
@@ -72,6 +122,20 @@ where
         self.buffer[wrapped_idx]
     }
 
+    // turns the count newest items into the oldest ones, so that the next
+    // puts overwrite them first
+    pub(crate) fn rewind(&mut self, count: usize) {
+        if N != 0 {
+            self.head = Self::wrap_idx(self.head + N - count % N);
+        }
+    }
+
+    // (oldest part, newest part), concatenated they are in logical order
+    pub fn as_slices(&self) -> (&[T], &[T]) {
+        let (newer, older) = self.buffer.split_at(self.head);
+        (older, newer)
+    }
+
     pub fn get(&self, idx: isize) -> T {
         // may result in wrong index if idx is near isize::MIN and N is not a power of 2
         let new_idx = usize::wrapping_add_signed(self.head + Self::MID, idx);
@@ -0,0 +1,317 @@
+use crate::float::Float;
+use crate::resample::kernel;
+use crate::RingBuffer;
+
+// Level relative to a full scale amplitude of 1. Silence is negative infinity.
+pub fn to_dbfs<F: Float>(level: F) -> F {
+    F::from_f64(20.0) * level.abs().log10()
+}
+
+pub fn from_dbfs<F: Float>(dbfs: F) -> F {
+    F::from_f64(10.0).powf(dbfs / F::from_f64(20.0))
+}
+
+fn max<F: Float>(a: F, b: F) -> F {
+    if b > a {
+        b
+    } else {
+        a
+    }
+}
+
+pub trait Meter {
+    type Sample: Float;
+
+    fn put(&mut self, sample: Self::Sample);
+
+    // linear level, full scale is 1
+    fn level(&self) -> Self::Sample;
+
+    fn reset(&mut self);
+
+    fn put_slice(&mut self, samples: &[Self::Sample]) {
+        for sample in samples {
+            self.put(*sample);
+        }
+    }
+
+    fn dbfs(&self) -> Self::Sample {
+        to_dbfs(self.level())
+    }
+}
+
+// RMS over the last N samples. Reported unweighted, so a full scale sine
+// reads -3 dBFS.
+#[derive(Debug, Clone)]
+pub struct Rms<F, const N: usize>
+where
+    F: Float,
+{
+    history: RingBuffer<F, N>,
+    count: usize,
+    sum_of_squares: F,
+    since_refresh: usize,
+}
+
+impl<F, const N: usize> Rms<F, N>
+where
+    F: Float,
+{
+    pub const fn new() -> Self {
+        Rms {
+            history: RingBuffer::new(F::ZERO),
+            count: 0,
+            sum_of_squares: F::ZERO,
+            since_refresh: 0,
+        }
+    }
+}
+
+impl<F, const N: usize> Default for Rms<F, N>
+where
+    F: Float,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F, const N: usize> Meter for Rms<F, N>
+where
+    F: Float,
+{
+    type Sample = F;
+
+    fn put(&mut self, sample: F) {
+        if N == 0 {
+            return;
+        }
+        let old = self.history.replace(sample);
+        self.count = usize::min(self.count + 1, N);
+        self.since_refresh += 1;
+        if self.since_refresh == N {
+            self.since_refresh = 0;
+            self.sum_of_squares = self.history.iter().fold(F::ZERO, |acc, x| acc + x * x);
+        } else {
+            self.sum_of_squares += sample * sample - old * old;
+        }
+    }
+
+    fn level(&self) -> F {
+        if self.count == 0 || self.sum_of_squares <= F::ZERO {
+            return F::ZERO;
+        }
+        (self.sum_of_squares / F::from_usize(self.count)).sqrt()
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+// One pole follower of the rectified signal with separate time constants,
+// given in samples, for rising and falling levels.
+#[derive(Debug, Clone)]
+pub struct EnvelopeFollower<F>
+where
+    F: Float,
+{
+    attack: F,
+    release: F,
+    envelope: F,
+}
+
+impl<F> EnvelopeFollower<F>
+where
+    F: Float,
+{
+    pub fn new(attack: F, release: F) -> Self {
+        EnvelopeFollower {
+            attack: Self::coefficient(attack),
+            release: Self::coefficient(release),
+            envelope: F::ZERO,
+        }
+    }
+
+    pub fn from_millis(attack: F, release: F, sample_rate: F) -> Self {
+        let samples_per_ms = sample_rate / F::from_f64(1000.0);
+        Self::new(attack * samples_per_ms, release * samples_per_ms)
+    }
+
+    fn coefficient(time: F) -> F {
+        if time <= F::ZERO {
+            F::ZERO
+        } else {
+            (-F::ONE / time).exp()
+        }
+    }
+}
+
+impl<F> Meter for EnvelopeFollower<F>
+where
+    F: Float,
+{
+    type Sample = F;
+
+    fn put(&mut self, sample: F) {
+        let x = sample.abs();
+        let coefficient = if x > self.envelope {
+            self.attack
+        } else {
+            self.release
+        };
+        self.envelope = x + coefficient * (self.envelope - x);
+    }
+
+    fn level(&self) -> F {
+        self.envelope
+    }
+
+    fn reset(&mut self) {
+        self.envelope = F::ZERO;
+    }
+}
+
+// Holds the highest absolute sample for hold samples, then decays it by a
+// constant factor per sample until a higher sample arrives.
+#[derive(Debug, Clone)]
+pub struct PeakHold<F>
+where
+    F: Float,
+{
+    hold: usize,
+    decay: F,
+    peak: F,
+    remaining: usize,
+}
+
+impl<F> PeakHold<F>
+where
+    F: Float,
+{
+    pub const fn new(hold: usize, decay: F) -> Self {
+        PeakHold {
+            hold,
+            decay,
+            peak: F::ZERO,
+            remaining: 0,
+        }
+    }
+
+    // decay given as a fall rate in dB per second
+    pub fn with_fall_rate(hold: usize, db_per_second: F, sample_rate: F) -> Self {
+        Self::new(hold, from_dbfs(-db_per_second / sample_rate))
+    }
+}
+
+impl<F> Meter for PeakHold<F>
+where
+    F: Float,
+{
+    type Sample = F;
+
+    fn put(&mut self, sample: F) {
+        let x = sample.abs();
+        if x >= self.peak {
+            self.peak = x;
+            self.remaining = self.hold;
+        } else if self.remaining > 0 {
+            self.remaining -= 1;
+        } else {
+            self.peak = max(self.peak * self.decay, x);
+        }
+    }
+
+    fn level(&self) -> F {
+        self.peak
+    }
+
+    fn reset(&mut self) {
+        self.peak = F::ZERO;
+        self.remaining = 0;
+    }
+}
+
+// Estimates the peak of the reconstructed analogue signal by upsampling by
+// FACTOR with a TAPS long windowed-sinc interpolator, as in ITU-R BS.1770.
+// The level is the maximum since the last reset.
+#[derive(Debug, Clone)]
+pub struct TruePeak<F, const TAPS: usize, const FACTOR: usize>
+where
+    F: Float,
+{
+    history: RingBuffer<F, TAPS>,
+    table: [[F; TAPS]; FACTOR],
+    peak: F,
+}
+
+impl<F, const TAPS: usize, const FACTOR: usize> TruePeak<F, TAPS, FACTOR>
+where
+    F: Float,
+{
+    pub fn new() -> Self {
+        const {
+            assert!(
+                TAPS > 0 && TAPS.is_multiple_of(2),
+                "TAPS must be even and non zero"
+            );
+            assert!(FACTOR > 0, "FACTOR must be non zero");
+        };
+        let mut table = [[F::ZERO; TAPS]; FACTOR];
+        for (p, row) in table.iter_mut().enumerate() {
+            let offset = (TAPS / 2 - 1) as f64 + p as f64 / FACTOR as f64;
+            for (k, coefficient) in row.iter_mut().enumerate() {
+                *coefficient = F::from_f64(kernel(offset - k as f64, 0.9, TAPS as f64));
+            }
+        }
+        TruePeak {
+            history: RingBuffer::new(F::ZERO),
+            table,
+            peak: F::ZERO,
+        }
+    }
+
+    // the largest interpolated magnitude between the two samples around
+    // the centre of the filter, which lags the input by TAPS / 2 samples
+    pub fn current(&self) -> F {
+        let sample = self.history.get_oldest(TAPS / 2 - 1).abs();
+        self.table.iter().fold(sample, |peak, row| {
+            let value = self
+                .history
+                .iter()
+                .zip(row)
+                .fold(F::ZERO, |sum, (x, c)| sum + x * *c);
+            max(peak, value.abs())
+        })
+    }
+}
+
+impl<F, const TAPS: usize, const FACTOR: usize> Default for TruePeak<F, TAPS, FACTOR>
+where
+    F: Float,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F, const TAPS: usize, const FACTOR: usize> Meter for TruePeak<F, TAPS, FACTOR>
+where
+    F: Float,
+{
+    type Sample = F;
+
+    fn put(&mut self, sample: F) {
+        self.history.put(sample);
+        self.peak = max(self.peak, self.current());
+    }
+
+    fn level(&self) -> F {
+        self.peak
+    }
+
+    fn reset(&mut self) {
+        self.history = RingBuffer::new(F::ZERO);
+        self.peak = F::ZERO;
+    }
+}
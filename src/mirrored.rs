@@ -0,0 +1,156 @@
+use core::ptr::NonNull;
+use std::io;
+
+// Byte ring whose storage is mapped twice back-to-back, so the bytes at
+// [head, head + capacity) are always one contiguous slice in logical order.
+pub struct MirroredRingBuffer {
+    ptr: NonNull<u8>,
+    capacity: usize,
+    head: usize,
+}
+
+unsafe impl Send for MirroredRingBuffer {}
+unsafe impl Sync for MirroredRingBuffer {}
+
+fn page_size() -> usize {
+    // SAFETY: sysconf has no preconditions
+    let size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+    if size > 0 {
+        size as usize
+    } else {
+        4096
+    }
+}
+
+impl MirroredRingBuffer {
+    // capacity is rounded up to a multiple of the page size
+    pub fn new(min_capacity: usize) -> io::Result<Self> {
+        let page = page_size();
+        let capacity = usize::max(min_capacity, 1)
+            .checked_next_multiple_of(page)
+            .ok_or_else(|| io::Error::from(io::ErrorKind::InvalidInput))?;
+        let len = capacity
+            .checked_mul(2)
+            .ok_or_else(|| io::Error::from(io::ErrorKind::InvalidInput))?;
+
+        // SAFETY: plain syscalls, every result is checked before use and the
+        // fixed mappings only ever replace our own reservation
+        unsafe {
+            let fd = libc::memfd_create(c"ringbuffer".as_ptr(), libc::MFD_CLOEXEC);
+            if fd < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            let result = Self::map(fd, capacity, len);
+            libc::close(fd);
+            let ptr = result?;
+            Ok(MirroredRingBuffer {
+                ptr,
+                capacity,
+                head: 0,
+            })
+        }
+    }
+
+    unsafe fn map(fd: libc::c_int, capacity: usize, len: usize) -> io::Result<NonNull<u8>> {
+        if libc::ftruncate(fd, capacity as libc::off_t) != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let base = libc::mmap(
+            core::ptr::null_mut(),
+            len,
+            libc::PROT_NONE,
+            libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+            -1,
+            0,
+        );
+        if base == libc::MAP_FAILED {
+            return Err(io::Error::last_os_error());
+        }
+        for half in [base, base.byte_add(capacity)] {
+            let mapped = libc::mmap(
+                half,
+                capacity,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_SHARED | libc::MAP_FIXED,
+                fd,
+                0,
+            );
+            if mapped == libc::MAP_FAILED {
+                let err = io::Error::last_os_error();
+                libc::munmap(base, len);
+                return Err(err);
+            }
+        }
+        Ok(NonNull::new_unchecked(base.cast()))
+    }
+
+    pub const fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn put(&mut self, item: u8) {
+        // SAFETY: head < capacity, inside the first mapping
+        unsafe { self.ptr.add(self.head).write(item) };
+        self.head = (self.head + 1) % self.capacity;
+    }
+
+    pub fn put_slice(&mut self, items: &[u8]) {
+        // only the last capacity bytes survive anyway
+        let skip = items.len().saturating_sub(self.capacity);
+        self.head = (self.head + skip % self.capacity) % self.capacity;
+        let items = &items[skip..];
+        // SAFETY: head + items.len() < 2 * capacity, the mirror takes the wrap
+        unsafe {
+            core::ptr::copy_nonoverlapping(
+                items.as_ptr(),
+                self.ptr.add(self.head).as_ptr(),
+                items.len(),
+            )
+        };
+        self.head = (self.head + items.len()) % self.capacity;
+    }
+
+    pub fn get_oldest(&self, idx: usize) -> u8 {
+        self.as_slice()[idx % self.capacity]
+    }
+
+    pub fn get_newest(&self, idx: usize) -> u8 {
+        self.as_slice()[self.capacity - 1 - idx % self.capacity]
+    }
+
+    // all bytes, oldest first
+    pub fn as_slice(&self) -> &[u8] {
+        // SAFETY: head < capacity, so the range ends inside the mirror
+        unsafe { core::slice::from_raw_parts(self.ptr.add(self.head).as_ptr(), self.capacity) }
+    }
+
+    // the oldest len bytes
+    pub fn oldest(&self, len: usize) -> &[u8] {
+        &self.as_slice()[..len]
+    }
+
+    // the newest len bytes, oldest first
+    pub fn newest(&self, len: usize) -> &[u8] {
+        &self.as_slice()[self.capacity - len..]
+    }
+
+    pub fn iter(&self) -> core::iter::Copied<core::slice::Iter<'_, u8>> {
+        self.as_slice().iter().copied()
+    }
+}
+
+impl Drop for MirroredRingBuffer {
+    fn drop(&mut self) {
+        // SAFETY: unmaps exactly the reservation created in new
+        unsafe { libc::munmap(self.ptr.as_ptr().cast(), 2 * self.capacity) };
+    }
+}
+
+impl core::fmt::Debug for MirroredRingBuffer {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("MirroredRingBuffer")
+            .field("capacity", &self.capacity)
+            .field("head", &self.head)
+            .finish()
+    }
+}
@@ -0,0 +1,170 @@
+use crate::sync::{AtomicUsize, Ordering, UnsafeCell};
+use core::mem::MaybeUninit;
+
+// Bounded lock-free multi-producer multi-consumer queue after Dmitry Vyukov.
+// Every slot carries a stamp (lap | index) telling whether it is ready to be
+// written (stamp == tail) or read (stamp == head + 1) in the current lap.
+struct Slot<T> {
+    stamp: AtomicUsize,
+    value: UnsafeCell<MaybeUninit<T>>,
+}
+
+pub struct MpmcQueue<T, const N: usize> {
+    buffer: [Slot<T>; N],
+    head: AtomicUsize,
+    tail: AtomicUsize,
+}
+
+unsafe impl<T: Send, const N: usize> Send for MpmcQueue<T, N> {}
+unsafe impl<T: Send, const N: usize> Sync for MpmcQueue<T, N> {}
+
+impl<T, const N: usize> MpmcQueue<T, N> {
+    // smallest power of two that can hold every index in 0..N
+    const ONE_LAP: usize = (N + 1).next_power_of_two();
+
+    pub fn new() -> Self {
+        MpmcQueue {
+            buffer: core::array::from_fn(|i| Slot {
+                stamp: AtomicUsize::new(i),
+                value: UnsafeCell::new(MaybeUninit::uninit()),
+            }),
+            head: AtomicUsize::new(0),
+            tail: AtomicUsize::new(0),
+        }
+    }
+
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    #[inline(always)]
+    fn index(pos: usize) -> usize {
+        pos & (Self::ONE_LAP - 1)
+    }
+
+    #[inline(always)]
+    fn next_pos(pos: usize) -> usize {
+        if Self::index(pos) + 1 < N {
+            pos + 1
+        } else {
+            (pos & !(Self::ONE_LAP - 1)).wrapping_add(Self::ONE_LAP)
+        }
+    }
+
+    pub fn push(&self, item: T) -> Result<(), T> {
+        if N == 0 {
+            return Err(item);
+        }
+        let mut tail = self.tail.load(Ordering::Relaxed);
+        loop {
+            let slot = &self.buffer[Self::index(tail)];
+            let stamp = slot.stamp.load(Ordering::Acquire);
+            let diff = stamp.wrapping_sub(tail) as isize;
+            if diff == 0 {
+                match self.tail.compare_exchange_weak(
+                    tail,
+                    Self::next_pos(tail),
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(_) => {
+                        // SAFETY: winning the cas grants exclusive write access to the slot
+                        slot.value.with_mut(|ptr| unsafe { (*ptr).write(item) });
+                        slot.stamp.store(tail.wrapping_add(1), Ordering::Release);
+                        return Ok(());
+                    }
+                    Err(current) => tail = current,
+                }
+            } else if diff < 0 {
+                // slot still holds the item of the previous lap
+                return Err(item);
+            } else {
+                tail = self.tail.load(Ordering::Relaxed);
+            }
+        }
+    }
+
+    pub fn pop(&self) -> Option<T> {
+        if N == 0 {
+            return None;
+        }
+        let mut head = self.head.load(Ordering::Relaxed);
+        loop {
+            let slot = &self.buffer[Self::index(head)];
+            let stamp = slot.stamp.load(Ordering::Acquire);
+            let diff = stamp.wrapping_sub(head.wrapping_add(1)) as isize;
+            if diff == 0 {
+                match self.head.compare_exchange_weak(
+                    head,
+                    Self::next_pos(head),
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(_) => {
+                        // SAFETY: the stamp proves the slot was written and the cas grants
+                        // exclusive read access to it
+                        let item = slot.value.with(|ptr| unsafe { (*ptr).assume_init_read() });
+                        slot.stamp
+                            .store(head.wrapping_add(Self::ONE_LAP), Ordering::Release);
+                        return Some(item);
+                    }
+                    Err(current) => head = current,
+                }
+            } else if diff < 0 {
+                // slot not yet written in this lap
+                return None;
+            } else {
+                head = self.head.load(Ordering::Relaxed);
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        loop {
+            let tail = self.tail.load(Ordering::SeqCst);
+            let head = self.head.load(Ordering::SeqCst);
+            if self.tail.load(Ordering::SeqCst) == tail {
+                let tix = Self::index(tail);
+                let hix = Self::index(head);
+                return if tix > hix {
+                    tix - hix
+                } else if tix < hix {
+                    N - hix + tix
+                } else if tail == head {
+                    0
+                } else {
+                    N
+                };
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len() == N
+    }
+}
+
+impl<T, const N: usize> Default for MpmcQueue<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> Drop for MpmcQueue<T, N> {
+    fn drop(&mut self) {
+        while self.pop().is_some() {}
+    }
+}
+
+impl<T, const N: usize> core::fmt::Debug for MpmcQueue<T, N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("MpmcQueue")
+            .field("capacity", &N)
+            .field("len", &self.len())
+            .finish()
+    }
+}
@@ -0,0 +1,326 @@
+use core::marker::PhantomData;
+use core::mem::{align_of, size_of};
+use core::ptr::NonNull;
+use core::sync::atomic::{compiler_fence, Ordering};
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::os::unix::io::AsRawFd;
+use std::path::Path;
+
+/// Plain-old-data that can be stored in and read back from a file as raw bytes.
+///
+/// # Safety
+///
+/// Every bit pattern must be a valid value and the type must hold no pointers.
+pub unsafe trait Pod: Copy + 'static {}
+
+macro_rules! impl_pod {
+    ( $( $t:ty ),* ) => {
+        $( unsafe impl Pod for $t {} )*
+    };
+}
+
+impl_pod!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64);
+
+unsafe impl<T: Pod, const M: usize> Pod for [T; M] {}
+
+const MAGIC: u64 = u64::from_le_bytes(*b"RINGBUF\0");
+const VERSION: u32 = 2;
+const DATA_OFFSET: usize = 128;
+
+#[repr(C)]
+struct Header {
+    magic: u64,
+    version: u32,
+    elem_size: u32,
+    capacity: u64,
+    head: u64,
+    len: u64,
+    // odd while a put or clear is in progress, twice the number of puts otherwise
+    seq: u64,
+    checksum: u64,
+    // head and len to roll back to if a put or clear is interrupted; not
+    // covered by the checksum since they are written before seq turns odd
+    undo_head: u64,
+    undo_len: u64,
+}
+
+impl Header {
+    fn compute_checksum(&self) -> u64 {
+        self.checksum_with_seq(self.seq)
+    }
+
+    // FNV-1a over every field but the checksum itself, with seq as given so
+    // the checksum can be stored before the final seq
+    fn checksum_with_seq(&self, seq: u64) -> u64 {
+        let fields = [
+            self.magic,
+            ((self.version as u64) << 32) | self.elem_size as u64,
+            self.capacity,
+            self.head,
+            self.len,
+            seq,
+        ];
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        for byte in fields.iter().flat_map(|f| f.to_le_bytes()) {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+        hash
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpenStatus {
+    Created,
+    Reopened,
+    // a put was interrupted; its slot and the oldest item it overwrote are dropped
+    RecoveredTornWrite { discarded: usize },
+}
+
+pub struct PersistentRingBuffer<T: Pod, const N: usize> {
+    map: NonNull<u8>,
+    status: OpenStatus,
+    _marker: PhantomData<T>,
+}
+
+unsafe impl<T: Pod + Send, const N: usize> Send for PersistentRingBuffer<T, N> {}
+unsafe impl<T: Pod + Sync, const N: usize> Sync for PersistentRingBuffer<T, N> {}
+
+fn invalid_data(msg: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+impl<T: Pod, const N: usize> PersistentRingBuffer<T, N> {
+    const FILE_SIZE: usize = DATA_OFFSET + N * size_of::<T>();
+
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        const { assert!(align_of::<T>() <= DATA_OFFSET) };
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+        let existing = file.metadata()?.len();
+        let created = existing == 0;
+        if created {
+            file.set_len(Self::FILE_SIZE as u64)?;
+        } else if existing != Self::FILE_SIZE as u64 {
+            return Err(invalid_data("file size does not match the ring layout"));
+        }
+        let map = Self::map(&file)?;
+        let mut ring = PersistentRingBuffer {
+            map,
+            status: OpenStatus::Created,
+            _marker: PhantomData,
+        };
+        // a crash between set_len and init_header leaves a zeroed header
+        if created || ring.header_is_zeroed() {
+            ring.init_header();
+        } else {
+            ring.status = ring.validate_header()?;
+        }
+        Ok(ring)
+    }
+
+    fn map(file: &File) -> io::Result<NonNull<u8>> {
+        // SAFETY: maps the whole file we just sized, the result is checked
+        let ptr = unsafe {
+            libc::mmap(
+                core::ptr::null_mut(),
+                Self::FILE_SIZE,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_SHARED,
+                file.as_raw_fd(),
+                0,
+            )
+        };
+        if ptr == libc::MAP_FAILED {
+            return Err(io::Error::last_os_error());
+        }
+        NonNull::new(ptr.cast()).ok_or_else(|| invalid_data("mmap returned null"))
+    }
+
+    #[inline(always)]
+    fn header(&self) -> &Header {
+        // SAFETY: the mapping starts with the page aligned header
+        unsafe { &*self.map.as_ptr().cast::<Header>() }
+    }
+
+    #[inline(always)]
+    fn header_mut(&mut self) -> &mut Header {
+        // SAFETY: see header, exclusive through &mut self
+        unsafe { &mut *self.map.as_ptr().cast::<Header>() }
+    }
+
+    #[inline(always)]
+    fn slot(&self, idx: usize) -> *mut T {
+        debug_assert!(idx < N);
+        // SAFETY: DATA_OFFSET is a multiple of align_of::<T>() and idx < N
+        unsafe { self.map.as_ptr().add(DATA_OFFSET).cast::<T>().add(idx) }
+    }
+
+    fn header_is_zeroed(&self) -> bool {
+        // SAFETY: the header lies within the mapping
+        let bytes = unsafe { core::slice::from_raw_parts(self.map.as_ptr(), size_of::<Header>()) };
+        bytes.iter().all(|&b| b == 0)
+    }
+
+    fn init_header(&mut self) {
+        let header = self.header_mut();
+        header.magic = MAGIC;
+        header.version = VERSION;
+        header.elem_size = size_of::<T>() as u32;
+        header.capacity = N as u64;
+        header.head = 0;
+        header.len = 0;
+        header.seq = 0;
+        header.checksum = header.compute_checksum();
+        header.undo_head = 0;
+        header.undo_len = 0;
+    }
+
+    fn validate_header(&mut self) -> io::Result<OpenStatus> {
+        let header = self.header_mut();
+        if header.magic != MAGIC || header.version != VERSION {
+            return Err(invalid_data("not a ring buffer file"));
+        }
+        if header.elem_size as usize != size_of::<T>() || header.capacity != N as u64 {
+            return Err(invalid_data("element size or capacity mismatch"));
+        }
+        if header.seq & 1 == 0 {
+            if header.checksum != header.compute_checksum() {
+                return Err(invalid_data("header checksum mismatch"));
+            }
+            return Ok(OpenStatus::Reopened);
+        }
+        // interrupted put or clear: roll back to the recorded state
+        if header.undo_head >= N as u64 || header.undo_len > N as u64 {
+            return Err(invalid_data("corrupted rollback state"));
+        }
+        let full = header.undo_len == N as u64;
+        header.head = header.undo_head;
+        header.len = header.undo_len - full as u64;
+        // seq stays odd until the rollback is complete, so it can be redone
+        let seq = header.seq - 1;
+        header.checksum = header.checksum_with_seq(seq);
+        compiler_fence(Ordering::SeqCst);
+        header.seq = seq;
+        Ok(OpenStatus::RecoveredTornWrite {
+            discarded: full as usize,
+        })
+    }
+
+    pub fn status(&self) -> OpenStatus {
+        self.status
+    }
+
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    pub fn len(&self) -> usize {
+        self.header().len as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // number of puts since the file was created
+    pub fn sequence(&self) -> u64 {
+        self.header().seq / 2
+    }
+
+    pub fn put(&mut self, item: T) {
+        if N == 0 {
+            return;
+        }
+        let header = self.header_mut();
+        let seq = header.seq;
+        header.undo_head = header.head;
+        header.undo_len = header.len;
+        compiler_fence(Ordering::SeqCst);
+        header.seq = seq + 1;
+        compiler_fence(Ordering::SeqCst);
+        let head = header.head as usize;
+        // SAFETY: head < N
+        unsafe { self.slot(head).write(item) };
+        compiler_fence(Ordering::SeqCst);
+        let header = self.header_mut();
+        header.head = ((head + 1) % N) as u64;
+        header.len = u64::min(header.len + 1, N as u64);
+        header.checksum = header.checksum_with_seq(seq + 2);
+        // until seq is stored the header reads as an interrupted put
+        compiler_fence(Ordering::SeqCst);
+        header.seq = seq + 2;
+    }
+
+    pub fn get_oldest(&self, idx: usize) -> Option<T> {
+        let len = self.len();
+        if idx >= len {
+            return None;
+        }
+        let start = self.header().head as usize + N - len;
+        // SAFETY: index wrapped into 0..N
+        Some(unsafe { self.slot((start + idx) % N).read() })
+    }
+
+    pub fn get_newest(&self, idx: usize) -> Option<T> {
+        let len = self.len();
+        if idx >= len {
+            return None;
+        }
+        self.get_oldest(len - 1 - idx)
+    }
+
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = T> + ExactSizeIterator + '_ {
+        (0..self.len()).map(|i| self.get_oldest(i).unwrap())
+    }
+
+    pub fn clear(&mut self) {
+        let header = self.header_mut();
+        let seq = header.seq;
+        // an interrupted clear rolls forward to the empty ring
+        header.undo_head = 0;
+        header.undo_len = 0;
+        compiler_fence(Ordering::SeqCst);
+        header.seq = seq + 1;
+        compiler_fence(Ordering::SeqCst);
+        header.head = 0;
+        header.len = 0;
+        header.checksum = header.checksum_with_seq(seq);
+        compiler_fence(Ordering::SeqCst);
+        header.seq = seq;
+    }
+
+    // blocks until the mapping has been written back to the file
+    pub fn flush(&self) -> io::Result<()> {
+        // SAFETY: syncs exactly our own mapping
+        let result =
+            unsafe { libc::msync(self.map.as_ptr().cast(), Self::FILE_SIZE, libc::MS_SYNC) };
+        if result != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+}
+
+impl<T: Pod, const N: usize> Drop for PersistentRingBuffer<T, N> {
+    fn drop(&mut self) {
+        // SAFETY: unmaps the mapping created in open
+        unsafe { libc::munmap(self.map.as_ptr().cast(), Self::FILE_SIZE) };
+    }
+}
+
+impl<T: Pod, const N: usize> core::fmt::Debug for PersistentRingBuffer<T, N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("PersistentRingBuffer")
+            .field("capacity", &N)
+            .field("len", &self.len())
+            .field("sequence", &self.sequence())
+            .field("status", &self.status)
+            .finish()
+    }
+}
@@ -0,0 +1,138 @@
+use crate::RingBuffer;
+use core::ops::Range;
+
+fn combine<T: Copy + PartialOrd>(a: (T, T), b: (T, T)) -> (T, T) {
+    let min = if b.0 < a.0 { b.0 } else { a.0 };
+    let max = if b.1 > a.1 { b.1 } else { a.1 };
+    (min, max)
+}
+
+// Multi-resolution (min, max) history for drawing waveforms. Level k is a
+// ring of the last N buckets of 2^k samples, aligned to multiples of 2^k
+// since the first sample, so level k reaches back N * 2^k samples. Samples
+// are numbered from 0 in the order they were put.
+#[derive(Debug, Clone)]
+pub struct MinMaxPyramid<T, const N: usize, const LEVELS: usize>
+where
+    T: Copy + PartialOrd,
+{
+    levels: [RingBuffer<(T, T), N>; LEVELS],
+    // pending[k] is the first half of the level k bucket being filled
+    pending: [Option<(T, T)>; LEVELS],
+    count: u64,
+}
+
+impl<T, const N: usize, const LEVELS: usize> MinMaxPyramid<T, N, LEVELS>
+where
+    T: Copy + PartialOrd,
+{
+    pub fn new(init: T) -> Self {
+        const {
+            assert!(N > 0, "N must be non zero");
+            assert!(LEVELS > 0 && LEVELS < 64, "LEVELS must be in 1..64");
+        };
+        MinMaxPyramid {
+            levels: core::array::from_fn(|_| RingBuffer::new((init, init))),
+            pending: [None; LEVELS],
+            count: 0,
+        }
+    }
+
+    // O(1) amortised, at most LEVELS buckets are completed per sample
+    pub fn put(&mut self, x: T) {
+        self.count += 1;
+        let mut bucket = (x, x);
+        for (level, ring) in self.levels.iter_mut().enumerate() {
+            if level > 0 {
+                match self.pending[level].take() {
+                    Some(first) => bucket = combine(first, bucket),
+                    None => {
+                        self.pending[level] = Some(bucket);
+                        return;
+                    }
+                }
+            }
+            ring.put(bucket);
+        }
+    }
+
+    pub fn put_slice(&mut self, samples: &[T]) {
+        for x in samples {
+            self.put(*x);
+        }
+    }
+
+    // Fills out with the (min, max) of out.len() equal parts of the sample
+    // range, None where nothing is known. Uses the coarsest level with at
+    // least one bucket per part that still reaches back to range.start, so
+    // the cost is O(out.len()) unless the top level is too fine for the
+    // range. Parts are widened to whole buckets of that level.
+    pub fn query(&self, range: Range<u64>, out: &mut [Option<(T, T)>]) {
+        let pixels = out.len() as u64;
+        if pixels == 0 {
+            return;
+        }
+        let span = range.end.saturating_sub(range.start);
+        let per_pixel = u64::max(span / pixels, 1);
+        let mut level = usize::min(per_pixel.ilog2() as usize, LEVELS - 1);
+        while level + 1 < LEVELS && range.start < self.first_sample(level) {
+            level += 1;
+        }
+        for (p, out) in (0..pixels).zip(out.iter_mut()) {
+            let start = range.start + (span as u128 * p as u128 / pixels as u128) as u64;
+            let end = range.start + (span as u128 * (p + 1) as u128 / pixels as u128) as u64;
+            *out = if start >= self.count {
+                None
+            } else {
+                let last = u64::max(end, start + 1).min(self.count) - 1;
+                (start >> level..=last >> level)
+                    .filter_map(|bucket| self.bucket(level, bucket))
+                    .reduce(combine)
+            };
+        }
+    }
+
+    // query over the last len samples
+    pub fn query_last(&self, len: u64, out: &mut [Option<(T, T)>]) {
+        self.query(self.count.saturating_sub(len)..self.count, out);
+    }
+
+    // (min, max) of bucket index at level, including the incomplete newest
+    // bucket
+    pub fn bucket(&self, level: usize, index: u64) -> Option<(T, T)> {
+        let complete = self.count >> level;
+        if index < complete {
+            let available = u64::min(N as u64, complete);
+            if index < complete - available {
+                return None;
+            }
+            Some(self.levels[level].get_newest((complete - 1 - index) as usize))
+        } else if index == complete {
+            // the pending halves below level hold exactly the samples since
+            // the last complete bucket
+            self.pending[1..=level]
+                .iter()
+                .flatten()
+                .copied()
+                .reduce(combine)
+        } else {
+            None
+        }
+    }
+
+    // oldest sample still covered by level
+    pub fn first_sample(&self, level: usize) -> u64 {
+        let complete = self.count >> level;
+        (complete - u64::min(N as u64, complete)) << level
+    }
+
+    // number of samples put
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    pub fn clear(&mut self) {
+        self.pending = [None; LEVELS];
+        self.count = 0;
+    }
+}
@@ -0,0 +1,337 @@
+use crate::timed::{TimedRingBuffer, Timestamp};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit<Ts> {
+    pub permits: u32,
+    pub window: Ts,
+}
+
+impl<Ts> RateLimit<Ts> {
+    pub const fn new(permits: u32, window: Ts) -> Self {
+        RateLimit { permits, window }
+    }
+}
+
+// Earliest time at which the denied request would be granted if nothing else
+// is acquired meanwhile. Requests larger than the limit saturate to the
+// largest representable timestamp.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryAfter<Ts>(pub Ts);
+
+pub trait RateLimiter {
+    type Timestamp: Timestamp;
+
+    fn try_acquire_weighted(
+        &mut self,
+        now: Self::Timestamp,
+        permits: u32,
+    ) -> Result<(), RetryAfter<Self::Timestamp>>;
+
+    fn try_acquire(&mut self, now: Self::Timestamp) -> Result<(), RetryAfter<Self::Timestamp>> {
+        self.try_acquire_weighted(now, 1)
+    }
+
+    fn reset(&mut self);
+}
+
+#[inline(always)]
+fn never<Ts: Timestamp>() -> RetryAfter<Ts> {
+    RetryAfter(Ts::from_ticks(u128::MAX))
+}
+
+#[inline(always)]
+fn later<Ts: Timestamp>(a: Option<Ts>, b: Ts) -> Option<Ts> {
+    Some(a.map_or(b, |a| Ts::max(a, b)))
+}
+
+// Exact limiter keeping one (timestamp, permits) entry per granted request.
+// N has to be at least the number of requests that can be granted within the
+// longest window, otherwise a full log denies further requests.
+#[derive(Debug, Clone)]
+pub struct SlidingWindowLog<Ts, const N: usize>
+where
+    Ts: Timestamp,
+{
+    log: TimedRingBuffer<Ts, u32, N>,
+    limit: RateLimit<Ts>,
+    burst: Option<RateLimit<Ts>>,
+    used: u64,
+}
+
+impl<Ts, const N: usize> SlidingWindowLog<Ts, N>
+where
+    Ts: Timestamp,
+{
+    pub fn new(limit: RateLimit<Ts>) -> Self {
+        SlidingWindowLog {
+            log: TimedRingBuffer::new(0),
+            limit,
+            burst: None,
+            used: 0,
+        }
+    }
+
+    // additionally caps the permits granted within the (shorter) burst window
+    pub fn with_burst(mut self, burst: RateLimit<Ts>) -> Self {
+        self.burst = Some(burst);
+        self
+    }
+
+    pub fn limit(&self) -> RateLimit<Ts> {
+        self.limit
+    }
+
+    pub fn burst(&self) -> Option<RateLimit<Ts>> {
+        self.burst
+    }
+
+    #[inline(always)]
+    fn first_active(&self, window: Ts, now: Ts) -> usize {
+        self.log
+            .partition_point(|ts| ts.saturating_add(window) <= now)
+    }
+
+    fn permits_since(&self, first: usize) -> u64 {
+        self.log.iter().skip(first).map(|(_, w)| w as u64).sum()
+    }
+
+    // time at which excess permits, counted from entry first on, have expired
+    fn release_time(&self, first: usize, excess: u64, window: Ts) -> Ts {
+        let mut freed = 0;
+        for (ts, weight) in self.log.iter().skip(first) {
+            freed += weight as u64;
+            if freed >= excess {
+                return ts.saturating_add(window);
+            }
+        }
+        never::<Ts>().0
+    }
+
+    fn expire(&mut self, now: Ts) {
+        let expired = self.first_active(self.limit.window, now);
+        let freed: u64 = self.log.iter().take(expired).map(|(_, w)| w as u64).sum();
+        self.used -= freed;
+        self.log.evict_oldest(expired);
+    }
+
+    // permits that could be acquired at now
+    pub fn available(&self, now: Ts) -> u32 {
+        let first = self.first_active(self.limit.window, now);
+        let mut available = (self.limit.permits as u64).saturating_sub(self.permits_since(first));
+        if let Some(burst) = self.burst {
+            let first = self.first_active(burst.window, now);
+            available = u64::min(
+                available,
+                (burst.permits as u64).saturating_sub(self.permits_since(first)),
+            );
+        }
+        if self.log.len() - first == N {
+            available = 0;
+        }
+        available as u32
+    }
+}
+
+impl<Ts, const N: usize> RateLimiter for SlidingWindowLog<Ts, N>
+where
+    Ts: Timestamp,
+{
+    type Timestamp = Ts;
+
+    fn try_acquire_weighted(&mut self, now: Ts, permits: u32) -> Result<(), RetryAfter<Ts>> {
+        if permits == 0 {
+            return Ok(());
+        }
+        if N == 0 || permits > self.limit.permits || self.burst.is_some_and(|b| permits > b.permits)
+        {
+            return Err(never());
+        }
+        self.expire(now);
+
+        let mut retry = None;
+        let requested = self.used + permits as u64;
+        if requested > self.limit.permits as u64 {
+            let excess = requested - self.limit.permits as u64;
+            retry = later(retry, self.release_time(0, excess, self.limit.window));
+        }
+        if let Some((oldest, _)) = self.log.get_oldest(0).filter(|_| self.log.len() == N) {
+            retry = later(retry, oldest.saturating_add(self.limit.window));
+        }
+        if let Some(burst) = self.burst {
+            let first = self.first_active(burst.window, now);
+            let requested = self.permits_since(first) + permits as u64;
+            if requested > burst.permits as u64 {
+                let excess = requested - burst.permits as u64;
+                retry = later(retry, self.release_time(first, excess, burst.window));
+            }
+        }
+
+        match retry {
+            Some(at) => Err(RetryAfter(at)),
+            None => {
+                self.log.put(now, permits);
+                self.used += permits as u64;
+                Ok(())
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        self.log.clear();
+        self.used = 0;
+    }
+}
+
+// Fixed windows aligned to multiples of the window length. The previous
+// window's count is weighted by how much of it still overlaps the sliding
+// window, which needs constant memory regardless of the permit count.
+#[derive(Debug, Clone, Copy)]
+struct WindowCounter<Ts> {
+    limit: RateLimit<Ts>,
+    start: u128,
+    current: u64,
+    previous: u64,
+}
+
+impl<Ts> WindowCounter<Ts>
+where
+    Ts: Timestamp,
+{
+    fn new(limit: RateLimit<Ts>) -> Self {
+        WindowCounter {
+            limit,
+            start: 0,
+            current: 0,
+            previous: 0,
+        }
+    }
+
+    #[inline(always)]
+    fn width(&self) -> u128 {
+        u128::max(self.limit.window.ticks(), 1)
+    }
+
+    fn roll(&mut self, now: u128) {
+        let width = self.width();
+        if now >= self.start + width {
+            let windows = (now - self.start) / width;
+            self.previous = if windows == 1 { self.current } else { 0 };
+            self.current = 0;
+            self.start += windows * width;
+        }
+    }
+
+    // earliest time in ticks at which previous * (width - elapsed) / width
+    // has shrunk to at most budget
+    fn drained(start: u128, width: u128, previous: u64, budget: u64) -> u128 {
+        let keep = budget as u128 * width / previous as u128;
+        start + width - u128::min(keep, width)
+    }
+
+    fn check(&self, now: u128, permits: u32) -> Result<(), u128> {
+        let width = self.width();
+        let limit = self.limit.permits as u64;
+        let elapsed = now.saturating_sub(self.start);
+        let requested = self.current + permits as u64;
+        let estimate =
+            requested as u128 * width + self.previous as u128 * (width - elapsed.min(width));
+        if estimate <= limit as u128 * width {
+            Ok(())
+        } else if requested <= limit {
+            Err(Self::drained(
+                self.start,
+                width,
+                self.previous,
+                limit - requested,
+            ))
+        } else {
+            // only the next window can grant it, with this one as previous
+            let budget = limit - permits as u64;
+            Err(Self::drained(
+                self.start + width,
+                width,
+                self.current,
+                budget,
+            ))
+        }
+    }
+
+    fn reset(&mut self) {
+        *self = WindowCounter::new(self.limit);
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SlidingWindowCounter<Ts> {
+    window: WindowCounter<Ts>,
+    burst: Option<WindowCounter<Ts>>,
+}
+
+impl<Ts> SlidingWindowCounter<Ts>
+where
+    Ts: Timestamp,
+{
+    pub fn new(limit: RateLimit<Ts>) -> Self {
+        SlidingWindowCounter {
+            window: WindowCounter::new(limit),
+            burst: None,
+        }
+    }
+
+    pub fn with_burst(mut self, burst: RateLimit<Ts>) -> Self {
+        self.burst = Some(WindowCounter::new(burst));
+        self
+    }
+
+    pub fn limit(&self) -> RateLimit<Ts> {
+        self.window.limit
+    }
+
+    pub fn burst(&self) -> Option<RateLimit<Ts>> {
+        self.burst.map(|b| b.limit)
+    }
+}
+
+impl<Ts> RateLimiter for SlidingWindowCounter<Ts>
+where
+    Ts: Timestamp,
+{
+    type Timestamp = Ts;
+
+    fn try_acquire_weighted(&mut self, now: Ts, permits: u32) -> Result<(), RetryAfter<Ts>> {
+        if permits == 0 {
+            return Ok(());
+        }
+        if permits > self.window.limit.permits
+            || self.burst.is_some_and(|b| permits > b.limit.permits)
+        {
+            return Err(never());
+        }
+        let now = now.ticks();
+        self.window.roll(now);
+        let mut retry = self.window.check(now, permits).err();
+        if let Some(burst) = self.burst.as_mut() {
+            burst.roll(now);
+            if let Err(at) = burst.check(now, permits) {
+                retry = Some(retry.map_or(at, |r| u128::max(r, at)));
+            }
+        }
+        match retry {
+            Some(at) => Err(RetryAfter(Ts::from_ticks(at))),
+            None => {
+                self.window.current += permits as u64;
+                if let Some(burst) = self.burst.as_mut() {
+                    burst.current += permits as u64;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        self.window.reset();
+        if let Some(burst) = self.burst.as_mut() {
+            burst.reset();
+        }
+    }
+}
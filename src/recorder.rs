@@ -0,0 +1,191 @@
+use crate::RingBuffer;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum State {
+    // recording, triggers are ignored
+    Disarmed,
+    // recording, waiting for a trigger
+    Armed,
+    // recording the remaining post-trigger events
+    Triggered { remaining: usize },
+    // capture complete, events are dropped until re-armed
+    Frozen,
+}
+
+// Records the last N events. Once armed and triggered it keeps recording
+// post_trigger more events and then freezes, so the capture holds up to
+// N - post_trigger events from before the trigger and post_trigger after.
+#[derive(Debug, Clone)]
+pub struct FlightRecorder<T, const N: usize>
+where
+    T: Copy,
+{
+    events: RingBuffer<T, N>,
+    len: usize,
+    post_trigger: usize,
+    // events recorded since the trigger
+    after: usize,
+    dropped: usize,
+    state: State,
+}
+
+impl<T, const N: usize> FlightRecorder<T, N>
+where
+    T: Copy,
+{
+    pub const fn new(init: T, post_trigger: usize) -> Self {
+        const { assert!(N > 0, "N must be non zero") };
+        assert!(post_trigger <= N, "post_trigger must not exceed N");
+        FlightRecorder {
+            events: RingBuffer::new(init),
+            len: 0,
+            post_trigger,
+            after: 0,
+            dropped: 0,
+            state: State::Disarmed,
+        }
+    }
+
+    pub fn put(&mut self, event: T) {
+        match self.state {
+            State::Frozen => {
+                self.dropped += 1;
+                return;
+            }
+            State::Triggered { remaining } => {
+                self.after += 1;
+                self.state = match remaining - 1 {
+                    0 => State::Frozen,
+                    remaining => State::Triggered { remaining },
+                };
+            }
+            State::Disarmed | State::Armed => {}
+        }
+        self.events.put(event);
+        self.len = usize::min(self.len + 1, N);
+    }
+
+    pub fn arm(&mut self) {
+        if self.state == State::Disarmed {
+            self.state = State::Armed;
+        }
+    }
+
+    pub fn disarm(&mut self) {
+        if self.state == State::Armed {
+            self.state = State::Disarmed;
+        }
+    }
+
+    // Marks the point after the latest event. Returns false unless armed.
+    pub fn trigger(&mut self) -> bool {
+        if self.state != State::Armed {
+            return false;
+        }
+        self.after = 0;
+        self.state = match self.post_trigger {
+            0 => State::Frozen,
+            remaining => State::Triggered { remaining },
+        };
+        true
+    }
+
+    // put followed by trigger, so event is the first pre-trigger one
+    pub fn put_and_trigger(&mut self, event: T) -> bool {
+        self.put(event);
+        self.trigger()
+    }
+
+    // Discards the capture and starts recording afresh, armed.
+    pub fn rearm(&mut self) {
+        self.len = 0;
+        self.after = 0;
+        self.dropped = 0;
+        self.state = State::Armed;
+    }
+
+    pub fn state(&self) -> State {
+        self.state
+    }
+
+    pub fn is_frozen(&self) -> bool {
+        self.state == State::Frozen
+    }
+
+    pub fn capture(&self) -> Option<Capture<'_, T, N>> {
+        self.is_frozen().then_some(Capture { recorder: self })
+    }
+
+    // recorded events, oldest first, whatever the state
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = T> + ExactSizeIterator + '_ {
+        let skip = N - self.len;
+        (skip..N).map(move |idx| self.events.get_oldest(idx))
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // events put while frozen
+    pub fn dropped(&self) -> usize {
+        self.dropped
+    }
+
+    pub fn post_trigger(&self) -> usize {
+        self.post_trigger
+    }
+
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+}
+
+// Frozen read-out in chronological order.
+#[derive(Debug, Clone, Copy)]
+pub struct Capture<'a, T, const N: usize>
+where
+    T: Copy,
+{
+    recorder: &'a FlightRecorder<T, N>,
+}
+
+impl<'a, T, const N: usize> Capture<'a, T, N>
+where
+    T: Copy,
+{
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = T> + ExactSizeIterator + 'a {
+        self.recorder.iter()
+    }
+
+    // index of the first post-trigger event, equal to the number of
+    // pre-trigger events
+    pub fn trigger_index(&self) -> usize {
+        self.recorder.len - self.recorder.after
+    }
+
+    pub fn pre_trigger(&self) -> impl DoubleEndedIterator<Item = T> + ExactSizeIterator + 'a {
+        let count = self.trigger_index();
+        self.iter().take(count)
+    }
+
+    pub fn post_trigger(&self) -> impl DoubleEndedIterator<Item = T> + ExactSizeIterator + 'a {
+        let count = self.trigger_index();
+        self.iter().skip(count)
+    }
+
+    pub fn get(&self, idx: usize) -> Option<T> {
+        self.iter().nth(idx)
+    }
+
+    pub fn len(&self) -> usize {
+        self.recorder.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.recorder.len == 0
+    }
+}
@@ -0,0 +1,143 @@
+use crate::float::Float;
+use crate::RingBuffer;
+
+// Least squares line through the last N samples. The oldest sample sits at
+// x = 0 and the newest at x = len - 1, so when the window slides every x
+// shifts down by one, which the sums account for without revisiting samples.
+#[derive(Debug, Clone)]
+pub struct WindowedRegression<F, const N: usize>
+where
+    F: Float,
+{
+    history: RingBuffer<F, N>,
+    len: usize,
+    sum_x: F,
+    sum_xx: F,
+    sum_y: F,
+    sum_yy: F,
+    sum_xy: F,
+    since_refresh: usize,
+}
+
+impl<F, const N: usize> WindowedRegression<F, N>
+where
+    F: Float,
+{
+    pub const fn new() -> Self {
+        WindowedRegression {
+            history: RingBuffer::new(F::ZERO),
+            len: 0,
+            sum_x: F::ZERO,
+            sum_xx: F::ZERO,
+            sum_y: F::ZERO,
+            sum_yy: F::ZERO,
+            sum_xy: F::ZERO,
+            since_refresh: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn put(&mut self, y: F) {
+        if N == 0 {
+            return;
+        }
+        let old = self.history.replace(y);
+        if self.len < N {
+            let x = F::from_usize(self.len);
+            self.len += 1;
+            self.sum_x += x;
+            self.sum_xx += x * x;
+            self.sum_xy += x * y;
+        } else {
+            // the oldest sample had x = 0, everything else moves one step down
+            self.sum_xy += F::from_usize(N - 1) * y - (self.sum_y - old);
+        }
+        self.sum_y += y - old;
+        self.sum_yy += y * y - old * old;
+        self.since_refresh += 1;
+        if self.since_refresh == N {
+            self.since_refresh = 0;
+            self.refresh();
+        }
+    }
+
+    fn refresh(&mut self) {
+        let (mut sum_y, mut sum_yy, mut sum_xy) = (F::ZERO, F::ZERO, F::ZERO);
+        for k in 0..self.len {
+            let y = self.history.get_newest(self.len - 1 - k);
+            sum_y += y;
+            sum_yy += y * y;
+            sum_xy += F::from_usize(k) * y;
+        }
+        self.sum_y = sum_y;
+        self.sum_yy = sum_yy;
+        self.sum_xy = sum_xy;
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    #[inline(always)]
+    fn covariance(&self) -> F {
+        F::from_usize(self.len) * self.sum_xy - self.sum_x * self.sum_y
+    }
+
+    #[inline(always)]
+    fn variance_x(&self) -> F {
+        F::from_usize(self.len) * self.sum_xx - self.sum_x * self.sum_x
+    }
+
+    #[inline(always)]
+    fn variance_y(&self) -> F {
+        F::from_usize(self.len) * self.sum_yy - self.sum_y * self.sum_y
+    }
+
+    // change per sample, None with fewer than two samples
+    pub fn slope(&self) -> Option<F> {
+        (self.len >= 2).then(|| self.covariance() / self.variance_x())
+    }
+
+    // fitted value at the oldest sample
+    pub fn intercept(&self) -> Option<F> {
+        let slope = self.slope()?;
+        Some((self.sum_y - slope * self.sum_x) / F::from_usize(self.len))
+    }
+
+    // coefficient of determination, None if it is undefined because all
+    // samples are equal
+    pub fn r_squared(&self) -> Option<F> {
+        if self.len < 2 {
+            return None;
+        }
+        let variance_y = self.variance_y();
+        if variance_y <= F::ZERO {
+            return None;
+        }
+        let covariance = self.covariance();
+        Some(covariance * covariance / (self.variance_x() * variance_y))
+    }
+
+    // extrapolates the line k samples past the newest one
+    pub fn predict(&self, k: usize) -> Option<F> {
+        let slope = self.slope()?;
+        let intercept = self.intercept()?;
+        Some(intercept + slope * F::from_usize(self.len - 1 + k))
+    }
+}
+
+impl<F, const N: usize> Default for WindowedRegression<F, N>
+where
+    F: Float,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
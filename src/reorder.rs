@@ -0,0 +1,212 @@
+use crate::sequence::SequenceNumber;
+use crate::timed::Timestamp;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Output<S, T> {
+    Item(S, T),
+    // count sequence numbers starting at first were given up on
+    Gap { first: S, count: usize },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InsertError<T> {
+    // already buffered
+    Duplicate(T),
+    // older than the next sequence number to be yielded
+    Late(T),
+    // N or more ahead of the next sequence number to be yielded
+    OutOfWindow(T),
+}
+
+// Re-sequences items that arrive out of order. Items are stored in the slot
+// for their distance from the next expected sequence number, so the window
+// covers N consecutive sequence numbers starting there. The first insert
+// sets the starting sequence number unless skip_to was called before.
+#[derive(Debug, Clone)]
+pub struct ReorderBuffer<S, Ts, T, const N: usize>
+where
+    S: SequenceNumber,
+    Ts: Timestamp,
+    T: Copy,
+{
+    slots: [Option<(Ts, T)>; N],
+    head: usize,
+    next: Option<S>,
+    // slots from head up to and including the furthest item
+    span: usize,
+    len: usize,
+    timeout: Ts,
+}
+
+impl<S, Ts, T, const N: usize> ReorderBuffer<S, Ts, T, N>
+where
+    S: SequenceNumber,
+    Ts: Timestamp,
+    T: Copy,
+{
+    // A missing item is skipped once any later item has waited timeout.
+    pub const fn new(timeout: Ts) -> Self {
+        ReorderBuffer {
+            slots: [None; N],
+            head: 0,
+            next: None,
+            span: 0,
+            len: 0,
+            timeout,
+        }
+    }
+
+    pub fn insert(&mut self, seq: S, item: T, now: Ts) -> Result<(), InsertError<T>> {
+        if N == 0 {
+            return Err(InsertError::OutOfWindow(item));
+        }
+        let next = *self.next.get_or_insert(seq);
+        let offset = next.distance_to(seq);
+        if offset < 0 {
+            return Err(InsertError::Late(item));
+        }
+        let offset = offset as usize;
+        if offset >= N {
+            return Err(InsertError::OutOfWindow(item));
+        }
+        let slot = &mut self.slots[(self.head + offset) % N];
+        if slot.is_some() {
+            return Err(InsertError::Duplicate(item));
+        }
+        *slot = Some((now, item));
+        self.len += 1;
+        self.span = usize::max(self.span, offset + 1);
+        Ok(())
+    }
+
+    // Yields the next item in sequence order. If it is missing, returns the
+    // run of missing sequence numbers once the timeout has elapsed, and None
+    // while still waiting.
+    pub fn pop(&mut self, now: Ts) -> Option<Output<S, T>> {
+        if N == 0 {
+            return None;
+        }
+        let next = self.next?;
+        if let Some((_, item)) = self.slots[self.head].take() {
+            self.len -= 1;
+            self.advance(1);
+            return Some(Output::Item(next, item));
+        }
+        let deadline = self.deadline()?;
+        if now < deadline {
+            return None;
+        }
+        let count = (0..self.span)
+            .take_while(|offset| self.slots[(self.head + offset) % N].is_none())
+            .count();
+        self.advance(count);
+        Some(Output::Gap { first: next, count })
+    }
+
+    // When pop will next skip missing items, if any are missing and a later
+    // item is buffered.
+    pub fn deadline(&self) -> Option<Ts> {
+        if self.len == 0 || self.slots.get(self.head)?.is_some() {
+            return None;
+        }
+        self.slots
+            .iter()
+            .flatten()
+            .map(|(arrival, _)| *arrival)
+            .min()
+            .map(|arrival| arrival.saturating_add(self.timeout))
+    }
+
+    // Runs of missing sequence numbers before the furthest buffered item, as
+    // (first, count).
+    pub fn gaps(&self) -> Gaps<'_, S, Ts, T, N> {
+        Gaps {
+            buffer: self,
+            offset: 0,
+        }
+    }
+
+    // Gives up on everything before seq, yielding nothing for it. Useful to
+    // resynchronise after an OutOfWindow insert.
+    pub fn skip_to(&mut self, seq: S) {
+        if N == 0 {
+            return;
+        }
+        let Some(next) = self.next else {
+            self.next = Some(seq);
+            return;
+        };
+        let offset = next.distance_to(seq);
+        if offset <= 0 {
+            return;
+        }
+        let offset = offset as usize;
+        for k in 0..usize::min(offset, N) {
+            if self.slots[(self.head + k) % N].take().is_some() {
+                self.len -= 1;
+            }
+        }
+        self.head = (self.head + offset) % N;
+        self.span = self.span.saturating_sub(offset);
+        self.next = Some(seq);
+    }
+
+    pub fn next_expected(&self) -> Option<S> {
+        self.next
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    pub fn timeout(&self) -> Ts {
+        self.timeout
+    }
+
+    // forgets all items and the starting sequence number
+    pub fn clear(&mut self) {
+        *self = Self::new(self.timeout);
+    }
+
+    fn advance(&mut self, count: usize) {
+        self.head = (self.head + count) % N;
+        self.span = self.span.saturating_sub(count);
+        self.next = self.next.map(|next| next.wrapping_add(count));
+    }
+}
+
+pub struct Gaps<'a, S, Ts, T, const N: usize>
+where
+    S: SequenceNumber,
+    Ts: Timestamp,
+    T: Copy,
+{
+    buffer: &'a ReorderBuffer<S, Ts, T, N>,
+    offset: usize,
+}
+
+impl<S, Ts, T, const N: usize> Iterator for Gaps<'_, S, Ts, T, N>
+where
+    S: SequenceNumber,
+    Ts: Timestamp,
+    T: Copy,
+{
+    type Item = (S, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let buffer = self.buffer;
+        let missing = |offset: &usize| buffer.slots[(buffer.head + offset) % N].is_none();
+        let start = (self.offset..buffer.span).find(missing)?;
+        let count = (start..buffer.span).take_while(missing).count();
+        self.offset = start + count;
+        Some((buffer.next?.wrapping_add(start), count))
+    }
+}
@@ -0,0 +1,125 @@
+use crate::sequence::SequenceNumber;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Replay {
+    // accepted before
+    Duplicate,
+    // too far behind the highest accepted sequence number to tell
+    TooOld,
+}
+
+// IPsec / DTLS style anti-replay window (RFC 6479). The bitmap is a ring of
+// W words indexed by an absolute position that keeps counting across
+// sequence number wraparound. Advancing clears whole words, at most W of
+// them, however far the window jumps. One word is kept as slack for that,
+// so the largest window is (W - 1) * 64.
+#[derive(Debug, Clone)]
+pub struct ReplayWindow<S, const W: usize>
+where
+    S: SequenceNumber,
+{
+    bitmap: [u64; W],
+    top: Option<S>,
+    // absolute position of top, never less than the largest window
+    position: u64,
+    window: u64,
+}
+
+impl<S, const W: usize> ReplayWindow<S, W>
+where
+    S: SequenceNumber,
+{
+    pub const MAX_WINDOW: usize = W.saturating_sub(1) * 64;
+
+    pub const fn new() -> Self {
+        Self::with_window(Self::MAX_WINDOW)
+    }
+
+    // window is the number of sequence numbers up to and including the
+    // highest accepted one that can still be accepted
+    pub const fn with_window(window: usize) -> Self {
+        assert!(W >= 2, "W must be at least 2");
+        assert!(
+            window > 0 && window <= Self::MAX_WINDOW,
+            "window out of range"
+        );
+        ReplayWindow {
+            bitmap: [0; W],
+            top: None,
+            position: W as u64 * 64,
+            window: window as u64,
+        }
+    }
+
+    pub const fn window(&self) -> usize {
+        self.window as usize
+    }
+
+    // the highest accepted sequence number
+    pub fn top(&self) -> Option<S> {
+        self.top
+    }
+
+    // Checks seq without recording it, so a packet can be authenticated
+    // before check_and_update records it.
+    pub fn check(&self, seq: S) -> Result<(), Replay> {
+        let Some(top) = self.top else {
+            return Ok(());
+        };
+        let distance = top.distance_to(seq);
+        if distance > 0 {
+            return Ok(());
+        }
+        let age = distance.unsigned_abs();
+        if age >= self.window {
+            return Err(Replay::TooOld);
+        }
+        let (word, bit) = self.locate(self.position - age);
+        if self.bitmap[word] & bit != 0 {
+            Err(Replay::Duplicate)
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn check_and_update(&mut self, seq: S) -> Result<(), Replay> {
+        self.check(seq)?;
+        let distance = match self.top {
+            Some(top) => top.distance_to(seq),
+            None => 1,
+        };
+        let position = if distance > 0 {
+            let position = self.position + distance as u64;
+            let current = self.position / 64;
+            let words = u64::min(position / 64 - current, W as u64);
+            for k in 1..=words {
+                self.bitmap[((current + k) % W as u64) as usize] = 0;
+            }
+            self.position = position;
+            self.top = Some(seq);
+            position
+        } else {
+            self.position - distance.unsigned_abs()
+        };
+        let (word, bit) = self.locate(position);
+        self.bitmap[word] |= bit;
+        Ok(())
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::with_window(self.window as usize);
+    }
+
+    fn locate(&self, position: u64) -> (usize, u64) {
+        (((position / 64) % W as u64) as usize, 1 << (position % 64))
+    }
+}
+
+impl<S, const W: usize> Default for ReplayWindow<S, W>
+where
+    S: SequenceNumber,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,245 @@
+use crate::float::Float;
+use crate::RingBuffer;
+use core::f64::consts::PI;
+
+// Output rate divided by input rate. Rational ratios are tracked exactly and
+// use one filter phase per output position; arbitrary ratios interpolate
+// between the nearest two phases.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Ratio {
+    Rational { up: u32, down: u32 },
+    Arbitrary(f64),
+}
+
+impl Ratio {
+    pub fn from_rates(input_rate: u32, output_rate: u32) -> Self {
+        assert!(
+            input_rate > 0 && output_rate > 0,
+            "sample rates must be non zero"
+        );
+        let divisor = gcd(input_rate, output_rate);
+        Ratio::Rational {
+            up: output_rate / divisor,
+            down: input_rate / divisor,
+        }
+    }
+
+    pub fn as_f64(&self) -> f64 {
+        match *self {
+            Ratio::Rational { up, down } => up as f64 / down as f64,
+            Ratio::Arbitrary(ratio) => ratio,
+        }
+    }
+}
+
+fn gcd(mut a: u32, mut b: u32) -> u32 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Position {
+    Rational { phase: u64, up: u64, down: u64 },
+    Arbitrary { phase: f64, step: f64 },
+}
+
+impl Position {
+    // moves to the next output and returns how many input samples to consume
+    fn advance(&mut self) -> usize {
+        match self {
+            Position::Rational { phase, up, down } => {
+                *phase += *down;
+                let whole = *phase / *up;
+                *phase %= *up;
+                whole as usize
+            }
+            Position::Arbitrary { phase, step } => {
+                *phase += *step;
+                let whole = libm::floor(*phase);
+                *phase -= whole;
+                whole as usize
+            }
+        }
+    }
+}
+
+// Windowed-sinc polyphase resampler. TAPS is the filter length and doubles as
+// the quality/latency knob: longer filters have a sharper cutoff and delay the
+// signal by TAPS / 2 input samples. PHASES is the number of precomputed filter
+// phases, which bounds the numerator of rational ratios and sets the
+// interpolation resolution for arbitrary ones.
+#[derive(Debug, Clone)]
+pub struct Resampler<F, const TAPS: usize, const PHASES: usize>
+where
+    F: Float,
+{
+    history: RingBuffer<F, TAPS>,
+    table: [[F; TAPS]; PHASES],
+    phases: usize,
+    ratio: Ratio,
+    position: Position,
+    pending: usize,
+}
+
+impl<F, const TAPS: usize, const PHASES: usize> Resampler<F, TAPS, PHASES>
+where
+    F: Float,
+{
+    // cutoff relative to the lower of the two Nyquist frequencies
+    const DEFAULT_CUTOFF: f64 = 0.95;
+
+    pub fn new(ratio: Ratio) -> Self {
+        Self::with_cutoff(ratio, Self::DEFAULT_CUTOFF)
+    }
+
+    pub fn with_cutoff(ratio: Ratio, cutoff: f64) -> Self {
+        const {
+            assert!(
+                TAPS > 0 && TAPS.is_multiple_of(2),
+                "TAPS must be even and non zero"
+            );
+            assert!(PHASES > 0, "PHASES must be non zero");
+        };
+        assert!(cutoff > 0.0 && cutoff <= 1.0, "cutoff must be in (0, 1]");
+        let (position, phases) = match ratio {
+            Ratio::Rational { up, down } => {
+                assert!(up > 0 && down > 0, "ratio terms must be non zero");
+                let divisor = gcd(up, down);
+                let (up, down) = ((up / divisor) as u64, (down / divisor) as u64);
+                assert!(up as usize <= PHASES, "ratio numerator exceeds PHASES");
+                (Position::Rational { phase: 0, up, down }, up as usize)
+            }
+            Ratio::Arbitrary(value) => {
+                assert!(value > 0.0 && value.is_finite(), "ratio must be positive");
+                (
+                    Position::Arbitrary {
+                        phase: 0.0,
+                        step: 1.0 / value,
+                    },
+                    PHASES,
+                )
+            }
+        };
+        let cutoff = cutoff * ratio.as_f64().min(1.0);
+
+        let mut table = [[F::ZERO; TAPS]; PHASES];
+        for (p, row) in table.iter_mut().take(phases).enumerate() {
+            // row p evaluates the output p / phases of the way from the sample
+            // just before the centre of the history to the one at the centre
+            let offset = (TAPS / 2 - 1) as f64 + p as f64 / phases as f64;
+            let mut taps = [0.0; TAPS];
+            for (k, tap) in taps.iter_mut().enumerate() {
+                *tap = kernel(offset - k as f64, cutoff, TAPS as f64);
+            }
+            // unity gain at DC for every phase
+            let sum: f64 = taps.iter().sum();
+            for (coefficient, tap) in row.iter_mut().zip(taps) {
+                *coefficient = F::from_f64(tap / sum);
+            }
+        }
+
+        Resampler {
+            history: RingBuffer::new(F::ZERO),
+            table,
+            phases,
+            ratio,
+            position,
+            pending: 1,
+        }
+    }
+
+    pub fn ratio(&self) -> Ratio {
+        self.ratio
+    }
+
+    // group delay in input samples
+    pub const fn latency(&self) -> usize {
+        TAPS / 2
+    }
+
+    // Resamples as much of input into output as possible, stopping when either
+    // runs out. Returns the number of input samples consumed and output
+    // samples produced; unconsumed input should be passed again next call.
+    pub fn process(&mut self, input: &[F], output: &mut [F]) -> (usize, usize) {
+        let mut consumed = 0;
+        let mut produced = 0;
+        loop {
+            while self.pending > 0 {
+                let Some(&sample) = input.get(consumed) else {
+                    return (consumed, produced);
+                };
+                self.history.put(sample);
+                consumed += 1;
+                self.pending -= 1;
+            }
+            let Some(out) = output.get_mut(produced) else {
+                return (consumed, produced);
+            };
+            *out = self.interpolate();
+            produced += 1;
+            self.pending = self.position.advance();
+        }
+    }
+
+    // upper bound on the samples process produces for input_len samples
+    pub fn output_len(&self, input_len: usize) -> usize {
+        libm::ceil(input_len as f64 * self.ratio.as_f64()) as usize + 1
+    }
+
+    pub fn reset(&mut self) {
+        self.history = RingBuffer::new(F::ZERO);
+        self.pending = 1;
+        match &mut self.position {
+            Position::Rational { phase, .. } => *phase = 0,
+            Position::Arbitrary { phase, .. } => *phase = 0.0,
+        }
+    }
+
+    fn interpolate(&self) -> F {
+        match self.position {
+            Position::Rational { phase, .. } => self.dot(&self.table[phase as usize]),
+            Position::Arbitrary { phase, .. } => {
+                let exact = phase * self.phases as f64;
+                let index = (exact as usize).min(self.phases - 1);
+                let t = F::from_f64(exact - index as f64);
+                let current = &self.table[index];
+                let mut sum = F::ZERO;
+                for (k, x) in self.history.iter().enumerate() {
+                    // the phase after the last is the first, one sample later
+                    let next = if index + 1 < self.phases {
+                        self.table[index + 1][k]
+                    } else if k > 0 {
+                        self.table[0][k - 1]
+                    } else {
+                        F::ZERO
+                    };
+                    let coefficient = current[k] + (next - current[k]) * t;
+                    sum += x * coefficient;
+                }
+                sum
+            }
+        }
+    }
+
+    fn dot(&self, row: &[F; TAPS]) -> F {
+        self.history
+            .iter()
+            .zip(row)
+            .fold(F::ZERO, |sum, (x, c)| sum + x * *c)
+    }
+}
+
+// Blackman windowed sinc spanning width samples, band limited to cutoff times
+// the sample rate / 2
+pub(crate) fn kernel(t: f64, cutoff: f64, width: f64) -> f64 {
+    if libm::fabs(t) >= width / 2.0 {
+        return 0.0;
+    }
+    let x = PI * cutoff * t;
+    let sinc = if x == 0.0 { 1.0 } else { libm::sin(x) / x };
+    let phase = 2.0 * PI * t / width;
+    let window = 0.42 + 0.5 * libm::cos(phase) + 0.08 * libm::cos(2.0 * phase);
+    cutoff * sinc * window
+}
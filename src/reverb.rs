@@ -0,0 +1,172 @@
+use crate::filter::Filter;
+use crate::float::Float;
+use crate::RingBuffer;
+
+// y[n] = x[n] + gain * x[n - N]
+#[derive(Debug, Clone)]
+pub struct FeedforwardComb<F, const N: usize>
+where
+    F: Float,
+{
+    delay: RingBuffer<F, N>,
+    gain: F,
+}
+
+impl<F, const N: usize> FeedforwardComb<F, N>
+where
+    F: Float,
+{
+    pub const fn new(gain: F) -> Self {
+        FeedforwardComb {
+            delay: RingBuffer::new(F::ZERO),
+            gain,
+        }
+    }
+
+    pub fn gain(&self) -> F {
+        self.gain
+    }
+
+    pub fn set_gain(&mut self, gain: F) {
+        self.gain = gain;
+    }
+}
+
+impl<F, const N: usize> Filter for FeedforwardComb<F, N>
+where
+    F: Float,
+{
+    type Sample = F;
+
+    fn process(&mut self, sample: F) -> F {
+        if N == 0 {
+            return sample;
+        }
+        let delayed = self.delay.replace(sample);
+        sample + self.gain * delayed
+    }
+
+    fn reset(&mut self) {
+        self.delay = RingBuffer::new(F::ZERO);
+    }
+}
+
+// Freeverb style lowpass-feedback comb: the output is the delay line tap,
+// which is fed back through a one pole lowpass. With damping 0 this is
+// y[n] = x[n - N] + feedback * y[n - N]; higher damping makes high frequencies
+// decay faster. Keep feedback below 1 for a stable filter.
+#[derive(Debug, Clone)]
+pub struct FeedbackComb<F, const N: usize>
+where
+    F: Float,
+{
+    delay: RingBuffer<F, N>,
+    feedback: F,
+    damping: F,
+    lowpass: F,
+}
+
+impl<F, const N: usize> FeedbackComb<F, N>
+where
+    F: Float,
+{
+    pub const fn new(feedback: F, damping: F) -> Self {
+        FeedbackComb {
+            delay: RingBuffer::new(F::ZERO),
+            feedback,
+            damping,
+            lowpass: F::ZERO,
+        }
+    }
+
+    pub fn feedback(&self) -> F {
+        self.feedback
+    }
+
+    pub fn set_feedback(&mut self, feedback: F) {
+        self.feedback = feedback;
+    }
+
+    pub fn damping(&self) -> F {
+        self.damping
+    }
+
+    // in [0, 1), 0 disables the lowpass
+    pub fn set_damping(&mut self, damping: F) {
+        self.damping = damping;
+    }
+}
+
+impl<F, const N: usize> Filter for FeedbackComb<F, N>
+where
+    F: Float,
+{
+    type Sample = F;
+
+    fn process(&mut self, sample: F) -> F {
+        if N == 0 {
+            return sample;
+        }
+        let output = self.delay.get_oldest(0);
+        self.lowpass = output * (F::ONE - self.damping) + self.lowpass * self.damping;
+        self.delay.put(sample + self.lowpass * self.feedback);
+        output
+    }
+
+    fn reset(&mut self) {
+        self.delay = RingBuffer::new(F::ZERO);
+        self.lowpass = F::ZERO;
+    }
+}
+
+// Schroeder allpass: y[n] = -gain * x[n] + x[n - N] + gain * y[n - N], built
+// from a single delay line v[n] = x[n] + gain * v[n - N].
+#[derive(Debug, Clone)]
+pub struct Allpass<F, const N: usize>
+where
+    F: Float,
+{
+    delay: RingBuffer<F, N>,
+    gain: F,
+}
+
+impl<F, const N: usize> Allpass<F, N>
+where
+    F: Float,
+{
+    pub const fn new(gain: F) -> Self {
+        Allpass {
+            delay: RingBuffer::new(F::ZERO),
+            gain,
+        }
+    }
+
+    pub fn gain(&self) -> F {
+        self.gain
+    }
+
+    pub fn set_gain(&mut self, gain: F) {
+        self.gain = gain;
+    }
+}
+
+impl<F, const N: usize> Filter for Allpass<F, N>
+where
+    F: Float,
+{
+    type Sample = F;
+
+    fn process(&mut self, sample: F) -> F {
+        if N == 0 {
+            return sample;
+        }
+        let delayed = self.delay.get_oldest(0);
+        let v = sample + self.gain * delayed;
+        self.delay.put(v);
+        delayed - self.gain * v
+    }
+
+    fn reset(&mut self) {
+        self.delay = RingBuffer::new(F::ZERO);
+    }
+}
@@ -0,0 +1,243 @@
+use crate::RingBuffer;
+use core::fmt;
+use core::iter::FusedIterator;
+use core::str::Utf8Error;
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Span {
+    start: usize,
+    len: usize,
+}
+
+// Keeps the last L lines of text written to it, as long as they fit in B
+// bytes. Each line is stored contiguously in a byte ring, moving to the
+// start when it reaches the end, and older lines are evicted as their bytes
+// are needed. Bytes past the first B of a single line are dropped. Input
+// need not be valid UTF-8 or end on a line or character boundary; the line
+// after the last newline is kept as a partial line that later writes extend.
+#[derive(Debug, Clone)]
+pub struct Scrollback<const B: usize, const L: usize> {
+    data: [u8; B],
+    spans: RingBuffer<Span, L>,
+    count: usize,
+    partial: bool,
+}
+
+impl<const B: usize, const L: usize> Scrollback<B, L> {
+    pub const fn new() -> Self {
+        Scrollback {
+            data: [0; B],
+            spans: RingBuffer::new(Span { start: 0, len: 0 }),
+            count: 0,
+            partial: false,
+        }
+    }
+
+    pub fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            if byte == b'\n' {
+                if self.partial {
+                    self.partial = false;
+                } else {
+                    self.push_line();
+                }
+            } else {
+                self.push_byte(byte);
+            }
+        }
+    }
+
+    // oldest to newest, including a partial last line
+    pub fn lines(&self) -> Lines<'_, B, L> {
+        Lines {
+            scrollback: self,
+            front: self.count,
+            back: 0,
+        }
+    }
+
+    // the last k lines, oldest first
+    pub fn tail(&self, k: usize) -> Lines<'_, B, L> {
+        Lines {
+            scrollback: self,
+            front: usize::min(k, self.count),
+            back: 0,
+        }
+    }
+
+    // line by age, 0 being the newest
+    pub fn get(&self, age: usize) -> Option<Line<'_>> {
+        if age >= self.count {
+            return None;
+        }
+        let span = self.spans.get_newest(age);
+        let bytes = &self.data[span.start..span.start + span.len];
+        Some(Line {
+            bytes: bytes.strip_suffix(b"\r").unwrap_or(bytes),
+        })
+    }
+
+    // whether the newest line has not been terminated yet
+    pub fn has_partial(&self) -> bool {
+        self.partial
+    }
+
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    // bytes held by the stored lines
+    pub fn bytes(&self) -> usize {
+        (0..self.count)
+            .map(|age| self.spans.get_newest(age).len)
+            .sum()
+    }
+
+    pub fn clear(&mut self) {
+        self.count = 0;
+        self.partial = false;
+    }
+
+    fn push_line(&mut self) -> bool {
+        if B == 0 || L == 0 {
+            return false;
+        }
+        let start = match self.count {
+            0 => 0,
+            _ => {
+                let newest = self.spans.get_newest(0);
+                (newest.start + newest.len) % B
+            }
+        };
+        self.count = usize::min(self.count + 1, L);
+        self.spans.put(Span { start, len: 0 });
+        true
+    }
+
+    fn push_byte(&mut self, byte: u8) {
+        if !self.partial {
+            if !self.push_line() {
+                return;
+            }
+            self.partial = true;
+        }
+        let mut line = self.spans.get_newest(0);
+        if line.len == B {
+            return;
+        }
+        if line.start + line.len == B {
+            self.evict_overlapping(0, line.len + 1);
+            self.data.copy_within(line.start..B, 0);
+            line.start = 0;
+        } else {
+            self.evict_overlapping(line.start + line.len, 1);
+        }
+        self.data[line.start + line.len] = byte;
+        line.len += 1;
+        self.spans.rewind(1);
+        self.spans.put(line);
+    }
+
+    // Evicts the oldest lines while they overlap the bytes about to be
+    // written. Older lines follow the write position in the ring, so they
+    // are reached in order.
+    fn evict_overlapping(&mut self, start: usize, len: usize) {
+        let end = start + len;
+        while self.count > 1 {
+            let oldest = self.spans.get_newest(self.count - 1);
+            let overlaps = if oldest.len == 0 {
+                (start..end).contains(&oldest.start)
+            } else {
+                oldest.start < end && start < oldest.start + oldest.len
+            };
+            if !overlaps {
+                break;
+            }
+            self.count -= 1;
+        }
+    }
+}
+
+impl<const B: usize, const L: usize> Default for Scrollback<B, L> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const B: usize, const L: usize> fmt::Write for Scrollback<B, L> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.write(s.as_bytes());
+        Ok(())
+    }
+}
+
+// One line without its terminator or a trailing carriage return.
+// Displays invalid UTF-8 as U+FFFD.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Line<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Line<'a> {
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    pub fn to_str(&self) -> Result<&'a str, Utf8Error> {
+        core::str::from_utf8(self.bytes)
+    }
+}
+
+impl fmt::Display for Line<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for chunk in self.bytes.utf8_chunks() {
+            f.write_str(chunk.valid())?;
+            if !chunk.invalid().is_empty() {
+                f.write_str("\u{FFFD}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// Iterates lines from age front - 1 down to age back.
+pub struct Lines<'a, const B: usize, const L: usize> {
+    scrollback: &'a Scrollback<B, L>,
+    front: usize,
+    back: usize,
+}
+
+impl<'a, const B: usize, const L: usize> Iterator for Lines<'a, B, L> {
+    type Item = Line<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.front <= self.back {
+            return None;
+        }
+        self.front -= 1;
+        self.scrollback.get(self.front)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.front - self.back;
+        (len, Some(len))
+    }
+}
+
+impl<const B: usize, const L: usize> DoubleEndedIterator for Lines<'_, B, L> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.front <= self.back {
+            return None;
+        }
+        self.back += 1;
+        self.scrollback.get(self.back - 1)
+    }
+}
+
+impl<const B: usize, const L: usize> FusedIterator for Lines<'_, B, L> {}
+
+impl<const B: usize, const L: usize> ExactSizeIterator for Lines<'_, B, L> {}
@@ -0,0 +1,155 @@
+use crate::sync::{fence, AtomicBool, AtomicUsize, Ordering, UnsafeCell};
+use crate::RingBuffer;
+use core::mem::MaybeUninit;
+
+// Single writer, many readers. The sequence counter is odd while a write is in
+// progress; readers copy the whole ring and retry if the counter moved.
+pub struct SeqLockRingBuffer<T, const N: usize> {
+    seq: AtomicUsize,
+    ring: UnsafeCell<RingBuffer<T, N>>,
+    writer_taken: AtomicBool,
+    snapshots: AtomicUsize,
+    retries: AtomicUsize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SeqLockStats {
+    pub writes: usize,
+    pub snapshots: usize,
+    pub retries: usize,
+}
+
+unsafe impl<T: Copy + Send, const N: usize> Send for SeqLockRingBuffer<T, N> {}
+unsafe impl<T: Copy + Send, const N: usize> Sync for SeqLockRingBuffer<T, N> {}
+
+impl<T, const N: usize> SeqLockRingBuffer<T, N>
+where
+    T: Copy,
+{
+    pub fn new(init_value: T) -> Self {
+        Self::from_ring(RingBuffer::new(init_value))
+    }
+
+    pub fn from_ring(ring: RingBuffer<T, N>) -> Self {
+        SeqLockRingBuffer {
+            seq: AtomicUsize::new(0),
+            ring: UnsafeCell::new(ring),
+            writer_taken: AtomicBool::new(false),
+            snapshots: AtomicUsize::new(0),
+            retries: AtomicUsize::new(0),
+        }
+    }
+
+    // returns None while another writer handle is alive
+    pub fn writer(&self) -> Option<SeqLockWriter<'_, T, N>> {
+        self.writer_taken
+            .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .ok()
+            .map(|_| SeqLockWriter { lock: self })
+    }
+
+    // single read attempt, None if it raced with the writer
+    pub fn try_snapshot(&self) -> Option<[T; N]> {
+        let before = self.seq.load(Ordering::Acquire);
+        if before & 1 == 1 {
+            return None;
+        }
+        // The copy may observe a concurrent write and hold torn, invalid
+        // values, so it stays uninitialised until the sequence check passes.
+        // Volatile keeps the compiler from assuming no write happened.
+        // SAFETY: reads the ring's bytes into MaybeUninit, valid for any bits
+        let copy = self.ring.with(|ptr| unsafe {
+            core::ptr::read_volatile(ptr.cast::<MaybeUninit<RingBuffer<T, N>>>())
+        });
+        fence(Ordering::Acquire);
+        let after = self.seq.load(Ordering::Relaxed);
+        if before != after {
+            return None;
+        }
+        // SAFETY: no write overlapped the copy, so it is a valid ring
+        let copy = unsafe { copy.assume_init() };
+        Some(core::array::from_fn(|i| copy.get_oldest(i)))
+    }
+
+    pub fn snapshot(&self) -> [T; N] {
+        loop {
+            if let Some(items) = self.try_snapshot() {
+                self.snapshots.fetch_add(1, Ordering::Relaxed);
+                return items;
+            }
+            self.retries.fetch_add(1, Ordering::Relaxed);
+            core::hint::spin_loop();
+        }
+    }
+
+    pub fn stats(&self) -> SeqLockStats {
+        SeqLockStats {
+            writes: self.seq.load(Ordering::Relaxed) / 2,
+            snapshots: self.snapshots.load(Ordering::Relaxed),
+            retries: self.retries.load(Ordering::Relaxed),
+        }
+    }
+
+    pub fn reset_stats(&self) {
+        self.snapshots.store(0, Ordering::Relaxed);
+        self.retries.store(0, Ordering::Relaxed);
+    }
+
+    pub fn into_inner(self) -> RingBuffer<T, N> {
+        self.ring.with(|ptr| unsafe { (*ptr).clone() })
+    }
+}
+
+impl<T, const N: usize> Default for SeqLockRingBuffer<T, N>
+where
+    T: Default + Copy,
+{
+    fn default() -> Self {
+        Self::from_ring(RingBuffer::default())
+    }
+}
+
+pub struct SeqLockWriter<'a, T, const N: usize>
+where
+    T: Copy,
+{
+    lock: &'a SeqLockRingBuffer<T, N>,
+}
+
+impl<T, const N: usize> SeqLockWriter<'_, T, N>
+where
+    T: Copy,
+{
+    #[inline(always)]
+    fn write<R>(&mut self, f: impl FnOnce(&mut RingBuffer<T, N>) -> R) -> R {
+        let seq = self.lock.seq.load(Ordering::Relaxed);
+        self.lock.seq.store(seq.wrapping_add(1), Ordering::Relaxed);
+        fence(Ordering::Release);
+        // SAFETY: this handle is the only writer, readers validate their copy
+        let result = self.lock.ring.with_mut(|ptr| f(unsafe { &mut *ptr }));
+        self.lock.seq.store(seq.wrapping_add(2), Ordering::Release);
+        result
+    }
+
+    pub fn put(&mut self, item: T) {
+        self.write(|ring| ring.put(item))
+    }
+
+    pub fn replace(&mut self, item: T) -> T {
+        self.write(|ring| ring.replace(item))
+    }
+
+    pub fn get_newest(&self, idx: usize) -> T {
+        // SAFETY: reads can't race with writes made through this very handle
+        self.lock.ring.with(|ptr| unsafe { (*ptr).get_newest(idx) })
+    }
+}
+
+impl<T, const N: usize> Drop for SeqLockWriter<'_, T, N>
+where
+    T: Copy,
+{
+    fn drop(&mut self) {
+        self.lock.writer_taken.store(false, Ordering::Release);
+    }
+}
@@ -0,0 +1,35 @@
+use core::fmt::Debug;
+
+// Wrapping sequence numbers compared with serial number arithmetic
+// (RFC 1982): b is after a when it is less than half the number space ahead.
+pub trait SequenceNumber: Copy + Eq + Debug {
+    fn wrapping_add(self, n: usize) -> Self;
+
+    // signed distance from self forward to other, in
+    // [-2^(bits - 1), 2^(bits - 1))
+    fn distance_to(self, other: Self) -> i64;
+
+    fn is_before(self, other: Self) -> bool {
+        self.distance_to(other) > 0
+    }
+}
+
+macro_rules! impl_sequence_number {
+    ( $( $t:ty: $signed:ty ),* ) => {
+        $(
+            impl SequenceNumber for $t {
+                #[inline(always)]
+                fn wrapping_add(self, n: usize) -> Self {
+                    <$t>::wrapping_add(self, n as $t)
+                }
+
+                #[inline(always)]
+                fn distance_to(self, other: Self) -> i64 {
+                    other.wrapping_sub(self) as $signed as i64
+                }
+            }
+        )*
+    };
+}
+
+impl_sequence_number!(u8: i8, u16: i16, u32: i32, u64: i64);
@@ -1,3 +1,38 @@
+// Deterministic pseudo random numbers for test inputs (Knuth's MMIX LCG).
+#[cfg(test)]
+struct Lcg(u64);
+
+#[cfg(test)]
+impl Lcg {
+    fn new(seed: u64) -> Self {
+        Lcg(seed)
+    }
+
+    // the whole state; the high bits are the most random
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self
+            .0
+            .wrapping_mul(6_364_136_223_846_793_005)
+            .wrapping_add(1_442_695_040_888_963_407);
+        self.0
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        (self.next_u64() >> 33) % n
+    }
+
+    // uniform in [-0.5, 0.5)
+    fn noise(&mut self) -> f64 {
+        (self.next_u64() >> 40) as f64 / (1u64 << 24) as f64 - 0.5
+    }
+}
+
+// |a - b| within tolerance, relative to |b| once that exceeds 1
+#[cfg(test)]
+fn close(a: f64, b: f64, tolerance: f64) -> bool {
+    (a - b).abs() <= tolerance * f64::max(1.0, b.abs())
+}
+
 #[cfg(test)]
 mod tests {
     use crate::RingBuffer;
@@ -162,6 +197,46 @@ mod tests {
         test_variants!(t);
     }
 
+    #[test]
+    fn put_slice() {
+        fn t<const SIZE: usize>() {
+            for offset in 0..SIZE {
+                for len in 0..2 * SIZE + 1 {
+                    let mut buf: RingBuffer<i32, SIZE> = RingBuffer::default();
+                    let mut expected: RingBuffer<i32, SIZE> = RingBuffer::default();
+                    for i in 0..offset as i32 {
+                        buf.put(i);
+                        expected.put(i);
+                    }
+                    let items: [i32; 129] = core::array::from_fn(|i| 100 + i as i32);
+                    buf.put_slice(&items[..len]);
+                    for &item in &items[..len] {
+                        expected.put(item);
+                    }
+                    assert_eq!(buf.head, expected.head);
+                    assert_eq!(buf.buffer, expected.buffer);
+                }
+            }
+        }
+        test_variants!(t);
+    }
+
+    #[test]
+    fn as_slices() {
+        fn t<const SIZE: usize>() {
+            for offset in 0..SIZE {
+                let mut buf: RingBuffer<i32, SIZE> = RingBuffer::default();
+                for i in 0..(SIZE + offset) as i32 {
+                    buf.put(i);
+                }
+                let (older, newer) = buf.as_slices();
+                assert_eq!(older.len() + newer.len(), SIZE);
+                assert!(older.iter().chain(newer).copied().eq(buf.iter()));
+            }
+        }
+        test_variants!(t);
+    }
+
     #[test]
     fn into_iterator() {
         fn t<const SIZE: usize>() {
@@ -450,3 +525,2553 @@ mod tests {
         test_variants!(t);
     }
 }
+
+#[cfg(all(test, not(loom)))]
+mod mpmc {
+    use crate::mpmc::MpmcQueue;
+    use std::sync::Arc;
+    use std::thread;
+    use std::vec::Vec;
+
+    #[test]
+    fn push_pop() {
+        fn t<const SIZE: usize>() {
+            let queue: MpmcQueue<i32, SIZE> = MpmcQueue::new();
+            for round in 0..3 {
+                assert!(queue.is_empty());
+                assert_eq!(queue.pop(), None);
+                for i in 0..SIZE as i32 {
+                    assert_eq!(queue.push(i + round), Ok(()));
+                    assert_eq!(queue.len(), (i + 1) as usize);
+                }
+                assert!(queue.is_full());
+                assert_eq!(queue.push(-1), Err(-1));
+                for i in 0..SIZE as i32 {
+                    assert_eq!(queue.pop(), Some(i + round));
+                }
+            }
+        }
+        crate::test_variants!(t);
+    }
+
+    #[test]
+    fn interleaved() {
+        let queue: MpmcQueue<usize, 3> = MpmcQueue::new();
+        for i in 0..100 {
+            queue.push(i).unwrap();
+            queue.push(i + 1000).unwrap();
+            assert_eq!(queue.len(), 2);
+            assert_eq!(queue.pop(), Some(i));
+            assert_eq!(queue.pop(), Some(i + 1000));
+        }
+    }
+
+    #[test]
+    fn drops_remaining() {
+        let item = Arc::new(());
+        {
+            let queue: MpmcQueue<Arc<()>, 4> = MpmcQueue::new();
+            queue.push(item.clone()).unwrap();
+            queue.push(item.clone()).unwrap();
+            drop(queue.pop());
+            assert_eq!(Arc::strong_count(&item), 2);
+        }
+        assert_eq!(Arc::strong_count(&item), 1);
+    }
+
+    #[test]
+    fn stress() {
+        const PRODUCERS: usize = 4;
+        const CONSUMERS: usize = 4;
+        const ITEMS: usize = 20_000;
+
+        let queue: Arc<MpmcQueue<usize, 16>> = Arc::new(MpmcQueue::new());
+        let producers: Vec<_> = (0..PRODUCERS)
+            .map(|p| {
+                let queue = queue.clone();
+                thread::spawn(move || {
+                    for i in 0..ITEMS {
+                        let mut item = p * ITEMS + i;
+                        while let Err(back) = queue.push(item) {
+                            item = back;
+                            thread::yield_now();
+                        }
+                    }
+                })
+            })
+            .collect();
+        let consumers: Vec<_> = (0..CONSUMERS)
+            .map(|_| {
+                let queue = queue.clone();
+                thread::spawn(move || {
+                    let mut seen = Vec::new();
+                    while seen.len() < PRODUCERS * ITEMS / CONSUMERS {
+                        match queue.pop() {
+                            Some(item) => seen.push(item),
+                            None => thread::yield_now(),
+                        }
+                    }
+                    seen
+                })
+            })
+            .collect();
+        for producer in producers {
+            producer.join().unwrap();
+        }
+        let mut all: Vec<usize> = consumers
+            .into_iter()
+            .flat_map(|c| c.join().unwrap())
+            .collect();
+        all.sort_unstable();
+        assert!(all.iter().copied().eq(0..PRODUCERS * ITEMS));
+        assert!(queue.is_empty());
+    }
+}
+
+#[cfg(all(test, loom))]
+mod mpmc_loom {
+    use crate::mpmc::MpmcQueue;
+    use loom::sync::Arc;
+    use loom::thread;
+
+    #[test]
+    fn two_producers_one_consumer() {
+        loom::model(|| {
+            let queue: Arc<MpmcQueue<usize, 2>> = Arc::new(MpmcQueue::new());
+            let producers: [_; 2] = core::array::from_fn(|p| {
+                let queue = queue.clone();
+                thread::spawn(move || queue.push(p).unwrap())
+            });
+            let mut seen = [false; 2];
+            let mut count = 0;
+            while count < 2 {
+                match queue.pop() {
+                    Some(item) => {
+                        assert!(!seen[item]);
+                        seen[item] = true;
+                        count += 1;
+                    }
+                    None => thread::yield_now(),
+                }
+            }
+            for producer in producers {
+                producer.join().unwrap();
+            }
+            assert_eq!(queue.pop(), None);
+        });
+    }
+
+    #[test]
+    fn wraparound_two_consumers() {
+        loom::model(|| {
+            let queue: Arc<MpmcQueue<usize, 1>> = Arc::new(MpmcQueue::new());
+            queue.push(1).unwrap();
+            let consumer = {
+                let queue = queue.clone();
+                thread::spawn(move || queue.pop())
+            };
+            let pushed = queue.push(2).is_ok();
+            let mine = queue.pop();
+            let theirs = consumer.join().unwrap();
+            let mut total = mine.unwrap_or(0) + theirs.unwrap_or(0);
+            if let Some(rest) = queue.pop() {
+                total += rest;
+            }
+            assert_eq!(total, if pushed { 3 } else { 1 });
+        });
+    }
+}
+
+#[cfg(all(test, not(loom)))]
+mod seqlock {
+    use crate::seqlock::SeqLockRingBuffer;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::thread;
+
+    #[test]
+    fn snapshot_in_order() {
+        let lock: SeqLockRingBuffer<i32, 4> = SeqLockRingBuffer::default();
+        let mut writer = lock.writer().unwrap();
+        assert!(lock.writer().is_none());
+        for i in 0..6 {
+            writer.put(i);
+        }
+        assert_eq!(writer.replace(6), 2);
+        assert_eq!(writer.get_newest(0), 6);
+        drop(writer);
+        assert!(lock.writer().is_some());
+        assert_eq!(lock.snapshot(), [3, 4, 5, 6]);
+        let stats = lock.stats();
+        assert_eq!(stats.writes, 7);
+        assert_eq!(stats.snapshots, 1);
+        assert_eq!(stats.retries, 0);
+        assert_eq!(lock.into_inner().get_oldest(0), 3);
+    }
+
+    #[test]
+    fn concurrent_readers_see_no_tearing() {
+        const SIZE: usize = 64;
+        let lock: SeqLockRingBuffer<u64, SIZE> = SeqLockRingBuffer::new(0);
+        let done = AtomicBool::new(false);
+        thread::scope(|s| {
+            for _ in 0..3 {
+                s.spawn(|| {
+                    while !done.load(Ordering::Relaxed) {
+                        let items = lock.snapshot();
+                        // the writer only ever puts consecutive numbers
+                        for pair in items.windows(2) {
+                            assert!(pair[0] == 0 || pair[1] == pair[0] + 1);
+                        }
+                    }
+                });
+            }
+            let mut writer = lock.writer().unwrap();
+            for i in 1..=200_000 {
+                writer.put(i);
+            }
+            done.store(true, Ordering::Relaxed);
+        });
+        assert_eq!(lock.snapshot()[SIZE - 1], 200_000);
+        assert_eq!(lock.stats().writes, 200_000);
+    }
+}
+
+#[cfg(all(test, feature = "mirrored", target_os = "linux"))]
+mod mirrored {
+    use crate::mirrored::MirroredRingBuffer;
+    use std::collections::VecDeque;
+    use std::vec::Vec;
+
+    fn page_size() -> usize {
+        // SAFETY: sysconf has no preconditions
+        unsafe { libc::sysconf(libc::_SC_PAGESIZE) as usize }
+    }
+
+    #[test]
+    fn capacity_is_page_rounded() {
+        let page = page_size();
+        let buf = MirroredRingBuffer::new(1).unwrap();
+        assert_eq!(buf.capacity(), page);
+        assert!(buf.iter().all(|b| b == 0));
+        let big = MirroredRingBuffer::new(page + 1).unwrap();
+        assert_eq!(big.capacity(), 2 * page);
+    }
+
+    #[test]
+    fn contiguous_across_wrap() {
+        let mut buf = MirroredRingBuffer::new(1).unwrap();
+        let cap = buf.capacity();
+        let data: Vec<u8> = (0..cap + 100).map(|i| (i % 251) as u8).collect();
+        buf.put_slice(&data[..cap - 10]);
+        buf.put_slice(&data[cap - 10..cap + 100]);
+        assert_eq!(buf.as_slice(), &data[100..]);
+        assert_eq!(buf.newest(50), &data[data.len() - 50..]);
+        assert_eq!(buf.oldest(3), &data[100..103]);
+        assert_eq!(buf.get_newest(0), *data.last().unwrap());
+        assert_eq!(buf.get_oldest(0), data[100]);
+    }
+
+    #[test]
+    fn matches_ringbuffer() {
+        let mut buf = MirroredRingBuffer::new(4096).unwrap();
+        let cap = buf.capacity();
+        assert!(cap >= 4096 && cap.is_multiple_of(page_size()));
+        // a full ring of zeros, like the mapping starts out
+        let mut reference: VecDeque<u8> = std::iter::repeat_n(0, cap).collect();
+        let put = |reference: &mut VecDeque<u8>, x: u8| {
+            reference.pop_front();
+            reference.push_back(x);
+        };
+        for round in 0..50usize {
+            let len = round * 397 % (cap + cap / 4);
+            let chunk: Vec<u8> = (0..len).map(|i| (i + round) as u8).collect();
+            buf.put_slice(&chunk);
+            for &x in &chunk {
+                put(&mut reference, x);
+            }
+            buf.put(round as u8);
+            put(&mut reference, round as u8);
+            assert!(buf.iter().eq(reference.iter().copied()));
+        }
+    }
+}
+
+#[cfg(all(test, feature = "persistent", unix))]
+mod persistent {
+    use crate::persistent::{OpenStatus, PersistentRingBuffer};
+    use std::fs;
+    use std::io::{Seek, SeekFrom, Write};
+    use std::path::PathBuf;
+    use std::vec::Vec;
+
+    fn temp_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(std::format!(
+            "ringbuffer-{}-{}.ring",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    fn write_at(path: &PathBuf, offset: u64, bytes: &[u8]) {
+        let mut file = fs::OpenOptions::new().write(true).open(path).unwrap();
+        file.seek(SeekFrom::Start(offset)).unwrap();
+        file.write_all(bytes).unwrap();
+    }
+
+    #[test]
+    fn reopen_keeps_order() {
+        let path = temp_path("reopen");
+        {
+            let mut ring: PersistentRingBuffer<u32, 5> = PersistentRingBuffer::open(&path).unwrap();
+            assert_eq!(ring.status(), OpenStatus::Created);
+            assert!(ring.is_empty());
+            for i in 0..3 {
+                ring.put(i);
+            }
+            assert_eq!(ring.iter().collect::<Vec<_>>(), [0, 1, 2]);
+        }
+        {
+            let mut ring: PersistentRingBuffer<u32, 5> = PersistentRingBuffer::open(&path).unwrap();
+            assert_eq!(ring.status(), OpenStatus::Reopened);
+            assert_eq!(ring.iter().collect::<Vec<_>>(), [0, 1, 2]);
+            for i in 3..8 {
+                ring.put(i);
+            }
+            ring.flush().unwrap();
+        }
+        let ring: PersistentRingBuffer<u32, 5> = PersistentRingBuffer::open(&path).unwrap();
+        assert_eq!(ring.iter().collect::<Vec<_>>(), [3, 4, 5, 6, 7]);
+        assert_eq!(ring.get_newest(0), Some(7));
+        assert_eq!(ring.get_oldest(0), Some(3));
+        assert_eq!(ring.get_oldest(5), None);
+        assert_eq!(ring.sequence(), 8);
+        drop(ring);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn layout_mismatch() {
+        let path = temp_path("mismatch");
+        drop(PersistentRingBuffer::<u32, 5>::open(&path).unwrap());
+        assert!(PersistentRingBuffer::<u32, 6>::open(&path).is_err());
+        assert!(PersistentRingBuffer::<u64, 5>::open(&path).is_err());
+        fs::remove_file(&path).unwrap();
+    }
+
+    const HEAD_OFFSET: u64 = 24;
+    const SEQ_OFFSET: u64 = 40;
+    const CHECKSUM_OFFSET: u64 = 48;
+    const UNDO_OFFSET: u64 = 56;
+    const DATA_OFFSET: u64 = 128;
+
+    // what put writes before it is interrupted: the rollback state, an odd
+    // sequence number and part of the item in slot head
+    fn crash_in_put(path: &PathBuf, head: u64, len: u64, seq: u64) {
+        write_at(path, UNDO_OFFSET, &head.to_le_bytes());
+        write_at(path, UNDO_OFFSET + 8, &len.to_le_bytes());
+        write_at(path, SEQ_OFFSET, &(seq + 1).to_le_bytes());
+        write_at(path, DATA_OFFSET + head * 8, &100u32.to_le_bytes());
+    }
+
+    #[test]
+    fn torn_write_detected() {
+        let path = temp_path("torn");
+        {
+            let mut ring: PersistentRingBuffer<u64, 4> = PersistentRingBuffer::open(&path).unwrap();
+            for i in 0..6 {
+                ring.put(i);
+            }
+        }
+        // crash in the middle of the seventh put
+        crash_in_put(&path, 2, 4, 12);
+        {
+            let ring: PersistentRingBuffer<u64, 4> = PersistentRingBuffer::open(&path).unwrap();
+            assert_eq!(
+                ring.status(),
+                OpenStatus::RecoveredTornWrite { discarded: 1 }
+            );
+            assert_eq!(ring.iter().collect::<Vec<_>>(), [3, 4, 5]);
+        }
+        // a corrupted header without a pending put is rejected
+        write_at(&path, SEQ_OFFSET, &42u64.to_le_bytes());
+        assert!(PersistentRingBuffer::<u64, 4>::open(&path).is_err());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn torn_write_before_seq() {
+        let path = temp_path("torn-late");
+        {
+            let mut ring: PersistentRingBuffer<u64, 4> = PersistentRingBuffer::open(&path).unwrap();
+            for i in 0..6 {
+                ring.put(i);
+            }
+        }
+        // the seventh put stored head, len and checksum but not its final seq
+        crash_in_put(&path, 2, 4, 12);
+        write_at(&path, HEAD_OFFSET, &3u64.to_le_bytes());
+        write_at(&path, HEAD_OFFSET + 8, &4u64.to_le_bytes());
+        write_at(&path, CHECKSUM_OFFSET, &0x5eed_u64.to_le_bytes());
+        {
+            let ring: PersistentRingBuffer<u64, 4> = PersistentRingBuffer::open(&path).unwrap();
+            assert_eq!(
+                ring.status(),
+                OpenStatus::RecoveredTornWrite { discarded: 1 }
+            );
+            assert_eq!(ring.iter().collect::<Vec<_>>(), [3, 4, 5]);
+            assert_eq!(ring.sequence(), 6);
+        }
+        let ring: PersistentRingBuffer<u64, 4> = PersistentRingBuffer::open(&path).unwrap();
+        assert_eq!(ring.status(), OpenStatus::Reopened);
+        drop(ring);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn torn_clear() {
+        let path = temp_path("torn-clear-half");
+        {
+            let mut ring: PersistentRingBuffer<u64, 4> = PersistentRingBuffer::open(&path).unwrap();
+            for i in 0..6 {
+                ring.put(i);
+            }
+        }
+        // clear stored the empty ring and its checksum but not its final seq
+        write_at(&path, UNDO_OFFSET, &[0; 16]);
+        write_at(&path, SEQ_OFFSET, &13u64.to_le_bytes());
+        write_at(&path, HEAD_OFFSET, &[0; 16]);
+        write_at(&path, CHECKSUM_OFFSET, &0x5eed_u64.to_le_bytes());
+        let ring: PersistentRingBuffer<u64, 4> = PersistentRingBuffer::open(&path).unwrap();
+        assert_eq!(
+            ring.status(),
+            OpenStatus::RecoveredTornWrite { discarded: 0 }
+        );
+        assert!(ring.is_empty());
+        assert_eq!(ring.sequence(), 6);
+        drop(ring);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn zeroed_header_is_initialised() {
+        let path = temp_path("zeroed");
+        // a crash between sizing the file and writing its header
+        fs::write(&path, [0u8; DATA_OFFSET as usize + 4 * 8]).unwrap();
+        let mut ring: PersistentRingBuffer<u64, 4> = PersistentRingBuffer::open(&path).unwrap();
+        assert_eq!(ring.status(), OpenStatus::Created);
+        assert!(ring.is_empty());
+        ring.put(1);
+        drop(ring);
+        let ring: PersistentRingBuffer<u64, 4> = PersistentRingBuffer::open(&path).unwrap();
+        assert_eq!(ring.status(), OpenStatus::Reopened);
+        assert_eq!(ring.iter().collect::<Vec<_>>(), [1]);
+        drop(ring);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn torn_write_after_clear() {
+        let path = temp_path("torn-clear");
+        {
+            let mut ring: PersistentRingBuffer<u64, 4> = PersistentRingBuffer::open(&path).unwrap();
+            for i in 0..6 {
+                ring.put(i);
+            }
+            ring.clear();
+        }
+        crash_in_put(&path, 0, 0, 12);
+        let mut ring: PersistentRingBuffer<u64, 4> = PersistentRingBuffer::open(&path).unwrap();
+        assert_eq!(
+            ring.status(),
+            OpenStatus::RecoveredTornWrite { discarded: 0 }
+        );
+        assert!(ring.is_empty());
+        assert_eq!(ring.sequence(), 6);
+        ring.put(7);
+        assert_eq!(ring.iter().collect::<Vec<_>>(), [7]);
+        drop(ring);
+        fs::remove_file(&path).unwrap();
+    }
+}
+
+#[cfg(all(test, not(loom)))]
+mod bip {
+    use crate::bip::{BipBuffer, SpscBipBuffer};
+    use std::thread;
+    use std::vec::Vec;
+
+    #[test]
+    fn reserve_commit_read_release() {
+        let mut bip: BipBuffer<8> = BipBuffer::new();
+        assert!(bip.is_empty());
+        assert!(bip.reserve(9).is_none());
+        bip.reserve(6).unwrap().copy_from_slice(b"hello!");
+        assert_eq!(bip.read(), b"");
+        bip.commit(6);
+        assert_eq!(bip.read(), b"hello!");
+        bip.release(4);
+        assert_eq!(bip.read(), b"o!");
+        // does not fit at the end, wraps to the front
+        let region = bip.reserve(3).unwrap();
+        region[..2].copy_from_slice(b"ab");
+        bip.commit(2);
+        assert_eq!(bip.read(), b"o!");
+        // the writer never catches up with the reader
+        assert!(bip.reserve(2).is_none());
+        assert_eq!(bip.reserve(1).unwrap().len(), 1);
+        bip.commit(0);
+        bip.release(2);
+        assert_eq!(bip.read(), b"ab");
+        assert_eq!(bip.reserve(6).unwrap().len(), 6);
+        bip.commit(0);
+        bip.release(2);
+        assert!(bip.is_empty());
+        assert_eq!(bip.reserve(8).unwrap().len(), 8);
+    }
+
+    #[test]
+    fn records_stay_contiguous() {
+        let mut bip: BipBuffer<32> = BipBuffer::new();
+        let mut next_in = 0u8;
+        let mut next_out = 0u8;
+        for round in 0..1000usize {
+            let len = 1 + round % 11;
+            if let Some(region) = bip.reserve(len) {
+                region[0] = len as u8;
+                region[1..].fill(next_in);
+                bip.commit(len);
+                next_in = next_in.wrapping_add(1);
+            }
+            if round % 3 != 0 {
+                let record = bip.read();
+                if !record.is_empty() {
+                    let len = record[0] as usize;
+                    assert!(record.len() >= len);
+                    assert!(record[1..len].iter().all(|&b| b == next_out));
+                    bip.release(len);
+                    next_out = next_out.wrapping_add(1);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn spsc_threads() {
+        const RECORDS: usize = 20_000;
+        let mut bip: SpscBipBuffer<64> = SpscBipBuffer::new();
+        let (mut producer, mut consumer) = bip.split();
+        thread::scope(|s| {
+            s.spawn(move || {
+                for i in 0..RECORDS {
+                    let len = 1 + i % 13;
+                    loop {
+                        if let Some(region) = producer.reserve(len) {
+                            region[0] = len as u8;
+                            region[1..].fill(i as u8);
+                            producer.commit(len);
+                            break;
+                        }
+                        thread::yield_now();
+                    }
+                }
+            });
+            let mut lens = Vec::new();
+            while lens.len() < RECORDS {
+                let record = consumer.read();
+                if record.is_empty() {
+                    thread::yield_now();
+                    continue;
+                }
+                let len = record[0] as usize;
+                let i = lens.len();
+                assert_eq!(len, 1 + i % 13);
+                assert!(record[1..len].iter().all(|&b| b == i as u8));
+                consumer.release(len);
+                lens.push(len);
+            }
+        });
+    }
+}
+
+#[cfg(all(test, loom))]
+mod bip_loom {
+    use crate::bip::SpscBipBuffer;
+    use loom::thread;
+
+    #[test]
+    fn spsc_wraparound() {
+        loom::model(|| {
+            let bip: &'static mut SpscBipBuffer<4> = std::boxed::Box::leak(Default::default());
+            let (mut producer, mut consumer) = bip.split();
+            let handle = thread::spawn(move || {
+                let mut sent = 0u8;
+                while sent < 3 {
+                    if let Some(region) = producer.reserve(2) {
+                        region.fill(sent);
+                        producer.commit(2);
+                        sent += 1;
+                    } else {
+                        thread::yield_now();
+                    }
+                }
+            });
+            let mut received = 0u8;
+            while received < 3 {
+                let record = consumer.read();
+                if record.len() < 2 {
+                    assert!(record.is_empty());
+                    thread::yield_now();
+                    continue;
+                }
+                assert_eq!(&record[..2], &[received, received]);
+                consumer.release(2);
+                received += 1;
+            }
+            handle.join().unwrap();
+        });
+    }
+}
+
+#[cfg(test)]
+mod timed {
+    use crate::timed::TimedRingBuffer;
+    use core::cell::Cell;
+    use core::time::Duration;
+    use std::vec::Vec;
+
+    fn filled<const SIZE: usize>(count: u32) -> TimedRingBuffer<u32, u32, SIZE> {
+        let mut buf = TimedRingBuffer::default();
+        for i in 0..count {
+            // two entries per tick
+            buf.put(i / 2 * 10, i);
+        }
+        buf
+    }
+
+    #[test]
+    fn window_queries() {
+        let buf = filled::<8>(12);
+        assert_eq!(buf.len(), 8);
+        assert_eq!(buf.oldest_timestamp(), Some(20));
+        assert_eq!(buf.newest_timestamp(), Some(50));
+        assert_eq!(buf.count_since(0), 8);
+        assert_eq!(buf.count_since(40), 4);
+        assert_eq!(buf.count_since(41), 2);
+        assert_eq!(buf.count_since(51), 0);
+        let items: Vec<u32> = buf.iter_since(35).map(|(_, x)| x).collect();
+        assert_eq!(items, [8, 9, 10, 11]);
+        let items: Vec<u32> = buf.iter_within(50, 10).rev().map(|(_, x)| x).collect();
+        assert_eq!(items, [11, 10, 9, 8]);
+    }
+
+    #[test]
+    fn lookup() {
+        let buf = filled::<8>(12);
+        assert_eq!(buf.binary_search(30), Ok(2));
+        assert_eq!(buf.binary_search(35), Err(4));
+        assert_eq!(buf.binary_search(0), Err(0));
+        assert_eq!(buf.latest_at(30), Some((30, 7)));
+        assert_eq!(buf.latest_at(39), Some((30, 7)));
+        assert_eq!(buf.latest_at(19), None);
+        assert_eq!(buf.latest_at(99), Some((50, 11)));
+    }
+
+    #[test]
+    fn evict() {
+        let mut buf = filled::<8>(5);
+        assert_eq!(buf.len(), 5);
+        assert_eq!(buf.evict_older_than(10), 2);
+        assert_eq!(buf.get_oldest(0), Some((10, 2)));
+        assert_eq!(buf.get_newest(0), Some((20, 4)));
+        assert_eq!(buf.evict_older_than(5), 0);
+        buf.put(30, 5);
+        assert_eq!(buf.iter().map(|(_, x)| x).collect::<Vec<_>>(), [2, 3, 4, 5]);
+        assert_eq!(buf.evict_older_than(100), 4);
+        assert!(buf.is_empty());
+        assert_eq!(buf.get_oldest(0), None);
+        assert_eq!(buf.latest_at(100), None);
+    }
+
+    #[test]
+    fn clocks() {
+        let ticks = Cell::new(0u16);
+        let clock = || {
+            ticks.set(ticks.get() + 3);
+            ticks.get()
+        };
+        let mut buf: TimedRingBuffer<u16, char, 4> = TimedRingBuffer::new(' ');
+        for c in "abcdef".chars() {
+            buf.put_now(&clock, c);
+        }
+        assert_eq!(buf.get_oldest(0), Some((9, 'c')));
+        assert_eq!(buf.count_since(15), 2);
+
+        let mut buf: TimedRingBuffer<Duration, i32, 3> = TimedRingBuffer::default();
+        buf.put(Duration::from_millis(1500), 1);
+        buf.put(Duration::from_secs(4), 2);
+        buf.put(Duration::from_secs(7), 3);
+        let now = Duration::from_secs(9);
+        assert_eq!(buf.iter_within(now, Duration::from_secs(5)).count(), 2);
+        assert_eq!(buf.iter_within(now, Duration::from_secs(60)).count(), 3);
+    }
+
+    #[test]
+    fn zero_capacity() {
+        let mut buf: TimedRingBuffer<u32, i32, 0> = TimedRingBuffer::default();
+        buf.put(1, 1);
+        assert!(buf.is_empty());
+        assert_eq!(buf.count_since(0), 0);
+    }
+}
+
+#[cfg(test)]
+mod rate_limit {
+    use super::Lcg;
+    use crate::rate_limit::{
+        RateLimit, RateLimiter, RetryAfter, SlidingWindowCounter, SlidingWindowLog,
+    };
+    use core::time::Duration;
+
+    #[test]
+    fn log_window() {
+        let mut limiter: SlidingWindowLog<u64, 8> = SlidingWindowLog::new(RateLimit::new(3, 10));
+        assert_eq!(limiter.try_acquire(0), Ok(()));
+        assert_eq!(limiter.try_acquire(1), Ok(()));
+        assert_eq!(limiter.try_acquire(2), Ok(()));
+        assert_eq!(limiter.available(3), 0);
+        assert_eq!(limiter.try_acquire(3), Err(RetryAfter(10)));
+        assert_eq!(limiter.try_acquire(9), Err(RetryAfter(10)));
+        assert_eq!(limiter.available(11), 2);
+        assert_eq!(limiter.try_acquire(10), Ok(()));
+        assert_eq!(limiter.try_acquire_weighted(11, 2), Err(RetryAfter(12)));
+        assert_eq!(limiter.try_acquire_weighted(12, 2), Ok(()));
+        assert_eq!(
+            limiter.try_acquire_weighted(12, 4),
+            Err(RetryAfter(u64::MAX))
+        );
+        limiter.reset();
+        assert_eq!(limiter.try_acquire_weighted(12, 3), Ok(()));
+    }
+
+    #[test]
+    fn log_burst_and_capacity() {
+        let mut limiter: SlidingWindowLog<u32, 16> =
+            SlidingWindowLog::new(RateLimit::new(10, 100)).with_burst(RateLimit::new(2, 10));
+        assert_eq!(limiter.try_acquire(0), Ok(()));
+        assert_eq!(limiter.try_acquire(0), Ok(()));
+        assert_eq!(limiter.try_acquire(1), Err(RetryAfter(10)));
+        assert_eq!(limiter.try_acquire(10), Ok(()));
+        assert_eq!(limiter.try_acquire(10), Ok(()));
+        assert_eq!(limiter.try_acquire(15), Err(RetryAfter(20)));
+        assert_eq!(limiter.available(20), 2);
+
+        // a log smaller than the limit denies once it is full
+        let mut limiter: SlidingWindowLog<u32, 2> = SlidingWindowLog::new(RateLimit::new(5, 10));
+        assert_eq!(limiter.try_acquire(0), Ok(()));
+        assert_eq!(limiter.try_acquire(4), Ok(()));
+        assert_eq!(limiter.try_acquire(5), Err(RetryAfter(10)));
+        assert_eq!(limiter.try_acquire(10), Ok(()));
+    }
+
+    #[test]
+    fn counter_approximation() {
+        let mut limiter = SlidingWindowCounter::new(RateLimit::new(10, 100u64));
+        for _ in 0..10 {
+            assert_eq!(limiter.try_acquire(50), Ok(()));
+        }
+        assert_eq!(limiter.try_acquire(60), Err(RetryAfter(110)));
+        // 90% of the previous window still overlaps
+        assert_eq!(limiter.try_acquire(110), Ok(()));
+        assert_eq!(limiter.try_acquire(110), Err(RetryAfter(120)));
+        assert_eq!(limiter.try_acquire(250), Ok(()));
+        assert_eq!(limiter.try_acquire_weighted(250, 9), Err(RetryAfter(300)));
+        assert_eq!(limiter.try_acquire_weighted(250, 8), Ok(()));
+
+        let mut limiter = SlidingWindowCounter::new(RateLimit::new(2, Duration::from_secs(1)));
+        let now = Duration::from_millis(200);
+        assert_eq!(limiter.try_acquire_weighted(now, 2), Ok(()));
+        assert_eq!(
+            limiter.try_acquire(now),
+            Err(RetryAfter(Duration::from_millis(1500)))
+        );
+    }
+
+    fn retry_hints_hold<L: RateLimiter<Timestamp = u64> + Clone>(mut limiter: L) {
+        let mut random = Lcg::new(12345);
+        let mut now = 0;
+        for _ in 0..2000 {
+            let seed = random.next_u64();
+            now += seed >> 61;
+            let permits = 1 + (seed >> 40) as u32 % 3;
+            if let Err(RetryAfter(at)) = limiter.try_acquire_weighted(now, permits) {
+                assert!(at > now);
+                assert!(limiter.clone().try_acquire_weighted(at, permits).is_ok());
+                if at > now + 1 {
+                    assert!(limiter
+                        .clone()
+                        .try_acquire_weighted(at - 1, permits)
+                        .is_err());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn retry_hints() {
+        retry_hints_hold(SlidingWindowLog::<u64, 32>::new(RateLimit::new(7, 20)));
+        retry_hints_hold(
+            SlidingWindowLog::<u64, 32>::new(RateLimit::new(7, 20))
+                .with_burst(RateLimit::new(3, 4)),
+        );
+        retry_hints_hold(SlidingWindowCounter::new(RateLimit::new(7, 20)));
+        retry_hints_hold(
+            SlidingWindowCounter::new(RateLimit::new(7, 20)).with_burst(RateLimit::new(3, 4)),
+        );
+    }
+}
+
+#[cfg(test)]
+mod filter {
+    use super::close;
+    use crate::filter::{Ewma, Filter, Holt, MovingAverage, WeightedMovingAverage};
+    use std::vec::Vec;
+
+    #[test]
+    fn moving_averages_match_naive() {
+        fn t<const SIZE: usize>() {
+            let input: Vec<f64> = (0..200).map(|i| ((i * 37) % 23) as f64 - 7.5).collect();
+            let mut sma: MovingAverage<f64, SIZE> = MovingAverage::new();
+            let mut wma: WeightedMovingAverage<f64, SIZE> = WeightedMovingAverage::new();
+            for (n, &x) in input.iter().enumerate() {
+                let window = &input[(n + 1).saturating_sub(SIZE)..=n];
+                let (y_sma, y_wma) = (sma.process(x), wma.process(x));
+                if SIZE == 0 {
+                    assert_eq!((y_sma, y_wma), (x, x));
+                    continue;
+                }
+                let mean = window.iter().sum::<f64>() / window.len() as f64;
+                let weights = (window.len() * (window.len() + 1) / 2) as f64;
+                let weighted = window
+                    .iter()
+                    .enumerate()
+                    .map(|(k, v)| (k + 1) as f64 * v)
+                    .sum::<f64>()
+                    / weights;
+                assert!(close(y_sma, mean, 1e-9));
+                assert!(close(y_wma, weighted, 1e-9));
+            }
+        }
+        crate::test_variants!(t);
+    }
+
+    #[test]
+    fn ewma() {
+        let mut ewma = Ewma::new(0.5f32);
+        assert_eq!(ewma.value(), None);
+        assert_eq!(ewma.process(4.0), 4.0);
+        assert_eq!(ewma.process(0.0), 2.0);
+        assert_eq!(ewma.process(2.0), 2.0);
+        ewma.reset();
+        assert_eq!(ewma.process(8.0), 8.0);
+        assert_eq!(Ewma::<f64>::with_span(3).alpha(), 0.5);
+    }
+
+    #[test]
+    fn holt_follows_ramp() {
+        let mut holt = Holt::new(0.3, 0.2);
+        for i in 0..50 {
+            holt.process(3.0 * i as f64 + 1.0);
+        }
+        assert!(close(holt.level(), 148.0, 1e-9));
+        assert!(close(holt.trend(), 3.0, 1e-9));
+        assert!(close(holt.forecast(2), 154.0, 1e-9));
+    }
+
+    #[test]
+    fn swappable_blocks() {
+        fn smooth(filter: &mut dyn Filter<Sample = f32>, block: &mut [f32]) {
+            filter.reset();
+            filter.process_in_place(block);
+        }
+        let input = [1.0, 3.0, 5.0, 7.0];
+        let mut filters: [&mut dyn Filter<Sample = f32>; 3] = [
+            &mut MovingAverage::<f32, 2>::new(),
+            &mut Ewma::new(0.5),
+            &mut Holt::new(0.5, 0.5),
+        ];
+        let expected = [
+            [1.0, 2.0, 4.0, 6.0],
+            [1.0, 2.0, 3.5, 5.25],
+            [1.0, 3.0, 5.0, 7.0],
+        ];
+        for (filter, expected) in filters.iter_mut().zip(expected) {
+            let mut block = input;
+            smooth(*filter, &mut block);
+            assert_eq!(block, expected);
+            let mut output = [0.0; 4];
+            filter.reset();
+            filter.process_block(&input, &mut output);
+            assert_eq!(output, expected);
+        }
+    }
+}
+
+#[cfg(test)]
+mod regression {
+    use super::close;
+    use crate::regression::WindowedRegression;
+    use std::vec::Vec;
+
+    fn naive(window: &[f64]) -> (f64, f64, f64) {
+        let n = window.len() as f64;
+        let mean_x = (n - 1.0) / 2.0;
+        let mean_y = window.iter().sum::<f64>() / n;
+        let (mut sxy, mut sxx, mut syy) = (0.0, 0.0, 0.0);
+        for (x, y) in window.iter().enumerate() {
+            let (dx, dy) = (x as f64 - mean_x, y - mean_y);
+            sxy += dx * dy;
+            sxx += dx * dx;
+            syy += dy * dy;
+        }
+        let slope = sxy / sxx;
+        (slope, mean_y - slope * mean_x, sxy * sxy / (sxx * syy))
+    }
+
+    #[test]
+    fn matches_naive_fit() {
+        fn t<const SIZE: usize>() {
+            let input: Vec<f64> = (0..300)
+                .map(|i| 0.5 * i as f64 + ((i * 7919) % 13) as f64)
+                .collect();
+            let mut fit: WindowedRegression<f64, SIZE> = WindowedRegression::new();
+            for (n, &y) in input.iter().enumerate() {
+                fit.put(y);
+                let window = &input[(n + 1).saturating_sub(SIZE)..=n];
+                if SIZE < 2 || window.len() < 2 {
+                    assert_eq!(fit.slope(), None);
+                    continue;
+                }
+                let (slope, intercept, r_squared) = naive(window);
+                assert!(close(fit.slope().unwrap(), slope, 1e-6));
+                assert!(close(fit.intercept().unwrap(), intercept, 1e-6));
+                assert!(close(fit.r_squared().unwrap(), r_squared, 1e-6));
+            }
+        }
+        crate::test_variants!(t);
+    }
+
+    #[test]
+    fn exact_line() {
+        let mut fit: WindowedRegression<f32, 8> = WindowedRegression::default();
+        for i in 0..20 {
+            fit.put(100.0 - 2.0 * i as f32);
+        }
+        assert_eq!(fit.len(), 8);
+        assert_eq!(fit.slope(), Some(-2.0));
+        assert_eq!(fit.intercept(), Some(76.0));
+        assert_eq!(fit.r_squared(), Some(1.0));
+        assert_eq!(fit.predict(0), Some(62.0));
+        assert_eq!(fit.predict(5), Some(52.0));
+    }
+
+    #[test]
+    fn flat_signal() {
+        let mut fit: WindowedRegression<f64, 4> = WindowedRegression::new();
+        fit.put(3.0);
+        assert_eq!(fit.slope(), None);
+        fit.put(3.0);
+        fit.put(3.0);
+        assert_eq!(fit.slope(), Some(0.0));
+        assert_eq!(fit.r_squared(), None);
+        assert_eq!(fit.predict(10), Some(3.0));
+        fit.clear();
+        assert!(fit.is_empty());
+    }
+}
+
+#[cfg(test)]
+mod correlation {
+    use super::close;
+    use crate::correlation::{
+        autocorrelation, cross_correlation, Autocorrelator, CrossCorrelator, Normalization,
+    };
+    use crate::RingBuffer;
+    use std::vec::Vec;
+
+    fn signal(n: usize, seed: usize) -> Vec<f64> {
+        (0..n)
+            .map(|i| ((i * 7919 + seed) % 17) as f64 - 8.0)
+            .collect()
+    }
+
+    fn naive(x: &[f64], y: &[f64], lag: isize) -> f64 {
+        (0..x.len() as isize)
+            .filter(|n| (0..y.len() as isize).contains(&(n - lag)))
+            .map(|n| x[n as usize] * y[(n - lag) as usize])
+            .sum()
+    }
+
+    #[test]
+    fn batch_matches_naive() {
+        fn t<const SIZE: usize>() {
+            let x = signal(SIZE + 5, 1);
+            let y = signal(SIZE + 5, 4);
+            let mut rx: RingBuffer<f64, SIZE> = RingBuffer::default();
+            let mut ry: RingBuffer<f64, SIZE> = RingBuffer::default();
+            rx.put_slice(&x);
+            ry.put_slice(&y);
+            let (x, y) = (&x[5..], &y[5..]);
+
+            let mut out = [0.0; 70];
+            autocorrelation(&rx, 0, Normalization::Raw, &mut out);
+            for (lag, r) in out.iter().enumerate() {
+                assert!(close(*r, naive(x, x, lag as isize), 1e-9));
+            }
+            cross_correlation(&rx, &ry, -35, Normalization::Raw, &mut out);
+            for (i, r) in out.iter().enumerate() {
+                assert!(close(*r, naive(x, y, 35 - i as isize), 1e-9));
+            }
+        }
+        crate::test_variants!(t);
+    }
+
+    #[test]
+    fn normalizations() {
+        let mut ring: RingBuffer<f64, 4> = RingBuffer::default();
+        ring.put_slice(&[1.0, 2.0, 3.0, 4.0]);
+        let mut out = [0.0; 2];
+        autocorrelation(&ring, 0, Normalization::Raw, &mut out);
+        assert_eq!(out, [30.0, 20.0]);
+        autocorrelation(&ring, 0, Normalization::Biased, &mut out);
+        assert_eq!(out, [7.5, 5.0]);
+        autocorrelation(&ring, 0, Normalization::Unbiased, &mut out);
+        assert_eq!(out, [7.5, 20.0 / 3.0]);
+        autocorrelation(&ring, 0, Normalization::Coefficient, &mut out);
+        assert_eq!(out, [1.0, 20.0 / 30.0]);
+        let zeros: RingBuffer<f64, 4> = RingBuffer::default();
+        autocorrelation(&zeros, 0, Normalization::Coefficient, &mut out);
+        assert_eq!(out, [0.0, 0.0]);
+    }
+
+    #[test]
+    fn incremental_matches_batch() {
+        fn t<const SIZE: usize>() {
+            if SIZE == 0 {
+                return;
+            }
+            let mut auto: Autocorrelator<f64, SIZE, 6> = Autocorrelator::new();
+            let mut cross: CrossCorrelator<f64, SIZE, 6> = CrossCorrelator::new();
+            let mut rx: RingBuffer<f64, SIZE> = RingBuffer::default();
+            let mut ry: RingBuffer<f64, SIZE> = RingBuffer::default();
+            let (x, y) = (signal(3 * SIZE + 7, 2), signal(3 * SIZE + 7, 9));
+            for (&a, &b) in x.iter().zip(&y) {
+                auto.put(a);
+                cross.put(a, b);
+                rx.put(a);
+                ry.put(b);
+                for norm in [Normalization::Raw, Normalization::Coefficient] {
+                    let (mut expected, mut actual) = ([0.0; 6], [0.0; 6]);
+                    let lags = usize::min(6, SIZE);
+                    autocorrelation(&rx, 0, norm, &mut expected);
+                    auto.correlation(norm, &mut actual);
+                    for k in 0..lags {
+                        assert!(close(actual[k], expected[k], 1e-9));
+                    }
+                    cross_correlation(&rx, &ry, 0, norm, &mut expected);
+                    cross.correlation(norm, &mut actual);
+                    for k in 0..lags {
+                        assert!(close(actual[k], expected[k], 1e-9));
+                    }
+                }
+            }
+        }
+        crate::test_variants!(t);
+    }
+
+    #[test]
+    fn pitch_and_latency() {
+        const PERIOD: usize = 20;
+        let tone = |n: usize| (2.0 * core::f64::consts::PI * n as f64 / PERIOD as f64).sin();
+
+        let mut auto: Autocorrelator<f64, 256, 40> = Autocorrelator::new();
+        for n in 0..1000 {
+            auto.put(tone(n));
+        }
+        let mut out = [0.0; 40];
+        auto.correlation(Normalization::Unbiased, &mut out);
+        let peak = (5..40).max_by(|&a, &b| out[a].total_cmp(&out[b])).unwrap();
+        assert_eq!(peak, PERIOD);
+
+        let noise = signal(1000, 3);
+        let mut cross: CrossCorrelator<f64, 128, 16> = CrossCorrelator::new();
+        for n in 11..1000 {
+            cross.put(noise[n], noise[n - 11]);
+        }
+        assert_eq!(cross.peak_lag(), Some(11));
+        assert!(cross.lag(11, Normalization::Coefficient) > 0.85);
+    }
+}
+
+#[cfg(test)]
+mod dft {
+    use super::{close, Lcg};
+    use crate::dft::{bin_for_frequency, Complex, Goertzel, SlidingDft};
+    use core::f64::consts::PI;
+    use std::vec::Vec;
+
+    fn dft(window: &[f64], bin: f64) -> Complex<f64> {
+        let n = window.len() as f64;
+        window
+            .iter()
+            .enumerate()
+            .fold(Complex::default(), |acc, (j, &x)| {
+                acc + Complex::from_polar(x, -2.0 * PI * bin * j as f64 / n)
+            })
+    }
+
+    fn close_complex(a: Complex<f64>, b: Complex<f64>) -> bool {
+        close((a - b).norm(), 0.0, 1e-7 * f64::max(1.0, b.norm()))
+    }
+
+    fn noise(n: usize) -> Vec<f64> {
+        let mut random = Lcg::new(7);
+        (0..n).map(|_| random.noise()).collect()
+    }
+
+    #[test]
+    fn sliding_matches_direct() {
+        const SIZE: usize = 32;
+        let bins = [0, 1, 5, 16, 31];
+        let mut sdft: SlidingDft<f64, SIZE, 5> = SlidingDft::new(bins);
+        let input = noise(500);
+        for (n, &x) in input.iter().enumerate() {
+            sdft.put(x);
+            let mut window = [0.0; SIZE];
+            for (k, w) in window.iter_mut().rev().enumerate() {
+                *w = if k <= n { input[n - k] } else { 0.0 };
+            }
+            for (i, &k) in bins.iter().enumerate() {
+                assert!(close_complex(sdft.bin(i), dft(&window, k as f64)));
+            }
+        }
+        let before = sdft.bin(2);
+        sdft.recompute();
+        assert!(close_complex(sdft.bin(2), before));
+    }
+
+    #[test]
+    fn damped_tone() {
+        const SIZE: usize = 64;
+        let mut sdft: SlidingDft<f32, SIZE, 2> = SlidingDft::with_damping([8, 20], 0.9999);
+        for n in 0..100_000 {
+            sdft.put((2.0 * PI * 8.0 * n as f64 / SIZE as f64 + 0.3).cos() as f32);
+        }
+        // a unit cosine has magnitude N/2 in its bin
+        assert!((sdft.magnitude(0) - 32.0).abs() < 0.2);
+        assert!(sdft.magnitude(1) < 0.1);
+        // the newest sample sits at the end of the window, phase is relative to its start
+        let expected = 0.3 + 2.0 * PI * 8.0 * (100_000 - SIZE) as f64 / SIZE as f64;
+        let diff = Complex::from_polar(1.0, sdft.phase(0) as f64 - expected).arg();
+        assert!(diff.abs() < 1e-2);
+    }
+
+    #[test]
+    fn goertzel_blocks() {
+        let input = noise(300);
+        let mut goertzel = Goertzel::new(3.0, 100);
+        let mut fractional = Goertzel::new(7.25, 100);
+        for (n, &x) in input.iter().enumerate() {
+            let result = goertzel.put(x);
+            let fractional_result = fractional.put(x);
+            if (n + 1) % 100 == 0 {
+                let block = &input[n + 1 - 100..=n];
+                assert!(close_complex(result.unwrap(), dft(block, 3.0)));
+                assert!(close_complex(fractional_result.unwrap(), dft(block, 7.25)));
+            } else {
+                assert!(result.is_none());
+            }
+        }
+        assert_eq!(goertzel.magnitude(), goertzel.last().map(|c| c.norm()));
+        goertzel.reset();
+        assert_eq!(goertzel.phase(), None);
+    }
+
+    #[test]
+    fn dtmf_tone() {
+        let rate = 8000.0;
+        let mut row = Goertzel::from_frequency(770.0, rate, 205);
+        let mut col = Goertzel::from_frequency(1336.0, rate, 205);
+        let mut other = Goertzel::from_frequency(941.0, rate, 205);
+        for n in 0..205 {
+            let t = n as f64 / rate;
+            let x = (2.0 * PI * 770.0 * t).sin() + (2.0 * PI * 1336.0 * t).sin();
+            row.put(x);
+            col.put(x);
+            other.put(x);
+        }
+        assert!(row.magnitude().unwrap() > 80.0);
+        assert!(col.magnitude().unwrap() > 80.0);
+        assert!(other.magnitude().unwrap() < 20.0);
+        assert_eq!(bin_for_frequency(770.0, rate, 205), 20);
+    }
+}
+
+#[cfg(test)]
+mod framing {
+    use crate::framing::{cola_sum, overlap_save_valid, Framer, OverlapAdd, WindowKind};
+    use std::vec::Vec;
+
+    #[test]
+    fn windows() {
+        let hann: [f64; 8] = WindowKind::Hann.coefficients();
+        assert_eq!(hann[0], 0.0);
+        assert!((hann[4] - 1.0).abs() < 1e-12);
+        assert!((hann[2] - hann[6]).abs() < 1e-12);
+        let hamming: [f64; 4] = WindowKind::Hamming.coefficients();
+        assert!((hamming[0] - 0.08).abs() < 1e-12);
+        let blackman: [f32; 4] = WindowKind::Blackman.coefficients();
+        assert!(blackman[0].abs() < 1e-6);
+        assert_eq!(WindowKind::Rectangular.coefficients::<f32, 3>(), [1.0; 3]);
+        assert!((cola_sum(&hann, None, 4) - 1.0).abs() < 1e-12);
+        assert!((cola_sum(&hann, Some(&hann), 2) - 1.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn frames_at_hop_boundaries() {
+        let input: Vec<f32> = (0..40).map(|i| i as f32).collect();
+        let mut framer: Framer<f32, 8> = Framer::new(3, WindowKind::Rectangular);
+        let mut frames = Vec::new();
+        // odd block sizes that straddle hop boundaries
+        for block in input.chunks(5) {
+            framer.process(block, |frame| frames.push(*frame));
+        }
+        assert_eq!(frames.len(), 1 + (40 - 8) / 3);
+        for (k, frame) in frames.iter().enumerate() {
+            let start = (3 * k) as f32;
+            assert!(frame
+                .iter()
+                .enumerate()
+                .all(|(i, &x)| x == start + i as f32));
+        }
+
+        let mut framer: Framer<f32, 4> = Framer::with_window(4, [0.0, 1.0, 2.0, 3.0]);
+        assert_eq!(framer.put(5.0), None);
+        framer.process(&[5.0; 2], |_| unreachable!());
+        assert_eq!(framer.put(5.0), Some(&[0.0, 5.0, 10.0, 15.0]));
+        framer.reset();
+        assert_eq!(framer.put(5.0), None);
+    }
+
+    #[test]
+    fn overlap_add_reconstructs() {
+        const SIZE: usize = 16;
+        let input: Vec<f64> = (0..400).map(|i| ((i * 37) % 11) as f64 - 5.0).collect();
+        for (hop, synthesis) in [(8, false), (4, false), (4, true)] {
+            let mut framer: Framer<f64, SIZE> = Framer::new(hop, WindowKind::Hann);
+            let mut ola = if synthesis {
+                OverlapAdd::with_windows(hop, framer.window(), *framer.window())
+            } else {
+                let mut ola = OverlapAdd::new(hop);
+                ola.set_gain(1.0 / cola_sum(framer.window(), None, hop));
+                ola
+            };
+            let mut output = Vec::new();
+            framer.process(&input, |frame| output.extend_from_slice(ola.add(frame)));
+            // output[i] is input[i]; the first frames lack their predecessors
+            for (i, y) in output.iter().enumerate().skip(SIZE) {
+                assert!((y - input[i]).abs() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn overlap_save() {
+        let frame = [1, 2, 3, 4, 5, 6];
+        assert_eq!(overlap_save_valid(&frame, 2), &[5, 6]);
+    }
+}
+
+#[cfg(test)]
+mod frame_ring {
+    use crate::frame_ring::FrameRing;
+    use std::vec::Vec;
+
+    fn stereo() -> FrameRing<i32, 2, 4> {
+        let mut ring = FrameRing::default();
+        // left counts up, right counts down
+        ring.put_interleaved(&[0, 0, 1, -1, 2, -2]);
+        ring.put_planar([&[3, 4, 5], &[-3, -4, -5]]);
+        ring
+    }
+
+    #[test]
+    fn interleaved_and_planar() {
+        let ring = stereo();
+        assert_eq!(ring.channels(), 2);
+        assert_eq!(ring.get_newest_frame(0), [5, -5]);
+        assert_eq!(ring.get_oldest_frame(0), [2, -2]);
+        let mut block = [0; 6];
+        ring.read_interleaved(&mut block);
+        assert_eq!(block, [3, -3, 4, -4, 5, -5]);
+        let (mut left, mut right) = ([0; 4], [0; 4]);
+        ring.read_planar([&mut left, &mut right]);
+        assert_eq!(left, [2, 3, 4, 5]);
+        assert_eq!(right, [-2, -3, -4, -5]);
+        let mut ring = ring;
+        ring.put_frame([6, -6]);
+        assert_eq!(
+            ring.frames().collect::<Vec<_>>(),
+            [[3, -3], [4, -4], [5, -5], [6, -6]]
+        );
+    }
+
+    #[test]
+    fn per_channel_access() {
+        let ring = stereo();
+        assert_eq!(ring.get_newest(0, 0), 5);
+        assert_eq!(ring.get_newest(1, 1), -4);
+        assert_eq!(ring.get_oldest(1, 0), -2);
+        assert_eq!(ring.iter_channel(0).collect::<Vec<_>>(), [2, 3, 4, 5]);
+        assert_eq!(
+            ring.iter_channel(1).rev().collect::<Vec<_>>(),
+            [-5, -4, -3, -2]
+        );
+        let mut iter = ring.iter_channel(1);
+        assert_eq!(iter.len(), 4);
+        assert_eq!(iter.next(), Some(-2));
+        assert_eq!(iter.next_back(), Some(-5));
+        assert_eq!(iter.len(), 2);
+        assert_eq!(iter.nth(1), Some(-4));
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next_back(), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn misaligned_block() {
+        let mut ring: FrameRing<f32, 3, 8> = FrameRing::new(0.0);
+        ring.put_interleaved(&[1.0; 4]);
+    }
+}
+
+#[cfg(test)]
+mod reverb {
+    use crate::filter::Filter;
+    use crate::reverb::{Allpass, FeedbackComb, FeedforwardComb};
+    use std::vec::Vec;
+
+    fn impulse_response<F: Filter<Sample = f64>>(filter: &mut F, len: usize) -> Vec<f64> {
+        let mut block = std::vec![0.0; len];
+        block[0] = 1.0;
+        filter.process_in_place(&mut block);
+        block
+    }
+
+    #[test]
+    fn feedforward() {
+        let mut comb: FeedforwardComb<f64, 3> = FeedforwardComb::new(0.5);
+        assert_eq!(
+            impulse_response(&mut comb, 8),
+            [1.0, 0.0, 0.0, 0.5, 0.0, 0.0, 0.0, 0.0]
+        );
+        comb.set_gain(-1.0);
+        comb.reset();
+        assert_eq!(impulse_response(&mut comb, 4), [1.0, 0.0, 0.0, -1.0]);
+
+        let mut empty: FeedforwardComb<f64, 0> = FeedforwardComb::new(0.5);
+        assert_eq!(impulse_response(&mut empty, 3), [1.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn feedback() {
+        let mut comb: FeedbackComb<f64, 2> = FeedbackComb::new(0.5, 0.0);
+        assert_eq!(
+            impulse_response(&mut comb, 7),
+            [0.0, 0.0, 1.0, 0.0, 0.5, 0.0, 0.25]
+        );
+        // the lowpass in the loop makes the echoes decay faster and smear
+        let mut damped: FeedbackComb<f64, 2> = FeedbackComb::new(0.5, 0.5);
+        let ir = impulse_response(&mut damped, 7);
+        assert_eq!(&ir[..4], [0.0, 0.0, 1.0, 0.0]);
+        assert_eq!(ir[4], 0.25);
+        assert!(ir[5] > 0.0);
+    }
+
+    #[test]
+    fn allpass_is_lossless() {
+        let mut allpass: Allpass<f64, 5> = Allpass::new(0.7);
+        let ir = impulse_response(&mut allpass, 2000);
+        assert_eq!(ir[0], -0.7);
+        assert_eq!(ir[5], 1.0 - 0.49);
+        let energy: f64 = ir.iter().map(|x| x * x).sum();
+        assert!((energy - 1.0).abs() < 1e-9);
+    }
+}
+
+#[cfg(test)]
+mod resample {
+    use crate::resample::{Ratio, Resampler};
+    use std::vec::Vec;
+
+    fn run<const TAPS: usize, const PHASES: usize>(
+        resampler: &mut Resampler<f64, TAPS, PHASES>,
+        input: &[f64],
+        chunk: usize,
+    ) -> Vec<f64> {
+        let mut output = Vec::new();
+        let mut buffer = [0.0; 7];
+        for block in input.chunks(chunk) {
+            let mut rest = block;
+            while !rest.is_empty() {
+                let (consumed, produced) = resampler.process(rest, &mut buffer);
+                output.extend_from_slice(&buffer[..produced]);
+                rest = &rest[consumed..];
+            }
+        }
+        output
+    }
+
+    #[test]
+    fn ratio_from_rates() {
+        assert_eq!(
+            Ratio::from_rates(44_100, 48_000),
+            Ratio::Rational { up: 160, down: 147 }
+        );
+        assert_eq!(Ratio::from_rates(48_000, 16_000).as_f64(), 1.0 / 3.0);
+    }
+
+    #[test]
+    fn unity_ratio_is_a_delay() {
+        let mut resampler: Resampler<f64, 16, 1> =
+            Resampler::with_cutoff(Ratio::Rational { up: 1, down: 1 }, 1.0);
+        let input: Vec<f64> = (1..=100).map(|i| i as f64).collect();
+        let output = run(&mut resampler, &input, 13);
+        assert_eq!(output.len(), input.len());
+        let delay = resampler.latency();
+        for (y, x) in output[delay..].iter().zip(&input) {
+            assert!((y - x).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn output_count_and_dc_gain() {
+        let input = [1.0; 4410];
+        for ratio in [
+            Ratio::from_rates(44_100, 48_000),
+            Ratio::from_rates(48_000, 44_100),
+            Ratio::Arbitrary(1.2345),
+            Ratio::Arbitrary(0.5),
+        ] {
+            let mut resampler: Resampler<f64, 32, 160> = Resampler::new(ratio);
+            let output = run(&mut resampler, &input, 100);
+            let expected = input.len() as f64 * ratio.as_f64();
+            assert!((output.len() as f64 - expected).abs() <= 1.0);
+            assert!(output.len() <= resampler.output_len(input.len()));
+            for y in &output[64..] {
+                assert!((y - 1.0).abs() < 1e-6, "{:?}: {}", ratio, y);
+            }
+        }
+    }
+
+    #[test]
+    fn chunking_does_not_change_output() {
+        let input: Vec<f64> = (0..500).map(|i| libm::sin(i as f64 * 0.05)).collect();
+        let mut a: Resampler<f64, 24, 160> = Resampler::new(Ratio::from_rates(44_100, 48_000));
+        let mut b = a.clone();
+        assert_eq!(run(&mut a, &input, 500), run(&mut b, &input, 3));
+        a.reset();
+        b.reset();
+        assert_eq!(run(&mut a, &input, 1), run(&mut b, &input, 11));
+    }
+
+    #[test]
+    fn preserves_tone() {
+        // 1 kHz at 44.1 kHz, resampled to 48 kHz, compared with the ideal tone
+        // shifted by the filter latency
+        let freq = 1000.0 / 44_100.0;
+        let input: Vec<f64> = (0..4410)
+            .map(|i| libm::sin(2.0 * core::f64::consts::PI * freq * i as f64))
+            .collect();
+        for ratio in [
+            Ratio::from_rates(44_100, 48_000),
+            Ratio::Arbitrary(48_000.0 / 44_100.0),
+        ] {
+            let mut resampler: Resampler<f64, 32, 160> = Resampler::new(ratio);
+            let output = run(&mut resampler, &input, 64);
+            let step = 1.0 / ratio.as_f64();
+            for (n, y) in output.iter().enumerate().skip(100) {
+                let t = n as f64 * step - resampler.latency() as f64;
+                let ideal = libm::sin(2.0 * core::f64::consts::PI * freq * t);
+                assert!(
+                    (y - ideal).abs() < 1e-3,
+                    "{:?} {}: {} vs {}",
+                    ratio,
+                    n,
+                    y,
+                    ideal
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn downsampling_rejects_above_nyquist() {
+        // 20 kHz at 48 kHz has no place at 16 kHz
+        let freq = 20_000.0 / 48_000.0;
+        let input: Vec<f64> = (0..4800)
+            .map(|i| libm::sin(2.0 * core::f64::consts::PI * freq * i as f64))
+            .collect();
+        let mut resampler: Resampler<f64, 64, 1> =
+            Resampler::new(Ratio::from_rates(48_000, 16_000));
+        let output = run(&mut resampler, &input, 480);
+        let peak = output[64..].iter().fold(0.0f64, |m, y| m.max(y.abs()));
+        assert!(peak < 0.01, "{}", peak);
+    }
+}
+
+#[cfg(test)]
+mod meter {
+    use crate::meter::{from_dbfs, to_dbfs, EnvelopeFollower, Meter, PeakHold, Rms, TruePeak};
+    use core::f64::consts::PI;
+    use std::vec::Vec;
+
+    fn sine(freq: f64, phase: f64, len: usize) -> Vec<f64> {
+        (0..len)
+            .map(|i| libm::sin(2.0 * PI * freq * i as f64 + phase))
+            .collect()
+    }
+
+    #[test]
+    fn dbfs() {
+        assert_eq!(to_dbfs(1.0f64), 0.0);
+        assert!((to_dbfs(0.5f64) + 6.0206).abs() < 1e-4);
+        assert_eq!(to_dbfs(0.0f32), f32::NEG_INFINITY);
+        assert!((from_dbfs(to_dbfs(0.25f64)) - 0.25).abs() < 1e-12);
+    }
+
+    #[test]
+    fn rms() {
+        let mut meter: Rms<f64, 100> = Rms::new();
+        assert_eq!(meter.level(), 0.0);
+        meter.put_slice(&[0.5; 10]);
+        assert!((meter.level() - 0.5).abs() < 1e-12);
+        // a whole number of cycles of a full scale sine
+        meter.put_slice(&sine(0.05, 0.0, 1000));
+        assert!((meter.dbfs() + 3.0103).abs() < 1e-3);
+        meter.put_slice(&[0.0; 100]);
+        assert!(meter.level() < 1e-6);
+        meter.reset();
+        meter.put(-2.0);
+        assert_eq!(meter.level(), 2.0);
+    }
+
+    #[test]
+    fn envelope() {
+        let mut follower = EnvelopeFollower::new(10.0f64, 1000.0);
+        follower.put_slice(&[1.0; 10]);
+        // one time constant reaches 1 - 1/e
+        assert!((follower.level() - (1.0 - (-1.0f64).exp())).abs() < 1e-9);
+        follower.put_slice(&[-1.0; 200]);
+        let top = follower.level();
+        assert!(top > 0.99);
+        follower.put_slice(&[0.0; 1000]);
+        assert!((follower.level() - top * (-1.0f64).exp()).abs() < 1e-9);
+        follower.reset();
+        assert_eq!(follower.level(), 0.0);
+
+        let mut instant = EnvelopeFollower::from_millis(0.0f64, 0.0, 48_000.0);
+        instant.put(0.7);
+        assert_eq!(instant.level(), 0.7);
+    }
+
+    #[test]
+    fn peak_hold() {
+        let mut meter = PeakHold::new(2, 0.5f64);
+        meter.put_slice(&[0.2, -0.8, 0.1]);
+        assert_eq!(meter.level(), 0.8);
+        meter.put(0.1);
+        assert_eq!(meter.level(), 0.8);
+        meter.put(0.1);
+        assert_eq!(meter.level(), 0.4);
+        meter.put(0.3);
+        assert_eq!(meter.level(), 0.3);
+        meter.put(0.9);
+        assert_eq!(meter.level(), 0.9);
+
+        // 20 dB per second at 10 Hz falls 2 dB per sample
+        let mut meter = PeakHold::with_fall_rate(0, 20.0f64, 10.0);
+        meter.put(1.0);
+        meter.put(0.0);
+        assert!((meter.dbfs() + 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn true_peak() {
+        // a quarter sample rate sine sampled 45 degrees off its peaks never
+        // has a sample above 0.707, but the signal reaches 1
+        let signal = sine(0.25, PI / 4.0, 400);
+        let sample_peak = signal.iter().fold(0.0f64, |m, x| m.max(x.abs()));
+        assert!(sample_peak < 0.71);
+        let mut meter: TruePeak<f64, 48, 4> = TruePeak::new();
+        meter.put_slice(&signal);
+        assert!((meter.level() - 1.0).abs() < 0.02, "{}", meter.level());
+        assert!(meter.dbfs() > -0.2);
+
+        // well oversampled signals read the same as their sample peak
+        meter.reset();
+        let slow: Vec<f64> = sine(0.01, 0.0, 400).iter().map(|x| x * 0.5).collect();
+        meter.put_slice(&slow);
+        assert!((meter.level() - 0.5).abs() < 0.01, "{}", meter.level());
+    }
+}
+
+#[cfg(test)]
+mod reorder {
+    use crate::reorder::{InsertError, Output, ReorderBuffer};
+    use crate::sequence::SequenceNumber;
+    use std::vec::Vec;
+
+    fn drain<const N: usize>(
+        buffer: &mut ReorderBuffer<u16, u64, char, N>,
+        now: u64,
+    ) -> Vec<Output<u16, char>> {
+        core::iter::from_fn(|| buffer.pop(now)).collect()
+    }
+
+    #[test]
+    fn serial_arithmetic() {
+        assert_eq!(10u16.distance_to(12), 2);
+        assert_eq!(12u16.distance_to(10), -2);
+        assert_eq!(65_535u16.distance_to(1), 2);
+        assert_eq!(1u16.distance_to(65_535), -2);
+        assert!(250u8.is_before(4));
+        assert!(!4u8.is_before(250));
+        assert_eq!(u32::MAX.wrapping_add(3), 2);
+    }
+
+    #[test]
+    fn reorders() {
+        let mut buffer: ReorderBuffer<u16, u64, char, 8> = ReorderBuffer::new(100);
+        assert_eq!(buffer.pop(0), None);
+        buffer.insert(5, 'a', 0).unwrap();
+        buffer.insert(7, 'c', 0).unwrap();
+        buffer.insert(9, 'e', 0).unwrap();
+        assert_eq!(buffer.gaps().collect::<Vec<_>>(), [(6, 1), (8, 1)]);
+        assert_eq!(drain(&mut buffer, 1), [Output::Item(5, 'a')]);
+        buffer.insert(8, 'd', 2).unwrap();
+        buffer.insert(6, 'b', 2).unwrap();
+        assert_eq!(buffer.gaps().count(), 0);
+        assert_eq!(
+            drain(&mut buffer, 3),
+            [
+                Output::Item(6, 'b'),
+                Output::Item(7, 'c'),
+                Output::Item(8, 'd'),
+                Output::Item(9, 'e')
+            ]
+        );
+        assert!(buffer.is_empty());
+        assert_eq!(buffer.next_expected(), Some(10));
+    }
+
+    #[test]
+    fn rejects() {
+        let mut buffer: ReorderBuffer<u16, u64, char, 4> = ReorderBuffer::new(100);
+        buffer.insert(10, 'a', 0).unwrap();
+        assert_eq!(buffer.insert(10, 'b', 0), Err(InsertError::Duplicate('b')));
+        assert_eq!(
+            buffer.insert(14, 'c', 0),
+            Err(InsertError::OutOfWindow('c'))
+        );
+        buffer.insert(13, 'd', 0).unwrap();
+        buffer.pop(0);
+        assert_eq!(buffer.insert(10, 'e', 0), Err(InsertError::Late('e')));
+        assert_eq!(buffer.len(), 1);
+    }
+
+    #[test]
+    fn timeout_skips_gaps() {
+        let mut buffer: ReorderBuffer<u16, u64, char, 8> = ReorderBuffer::new(50);
+        buffer.insert(0, 'a', 0).unwrap();
+        buffer.insert(3, 'd', 10).unwrap();
+        buffer.insert(5, 'f', 20).unwrap();
+        assert_eq!(drain(&mut buffer, 10), [Output::Item(0, 'a')]);
+        assert_eq!(buffer.deadline(), Some(60));
+        assert_eq!(buffer.pop(59), None);
+        assert_eq!(
+            drain(&mut buffer, 60),
+            [Output::Gap { first: 1, count: 2 }, Output::Item(3, 'd')]
+        );
+        // the remaining item arrived later
+        assert_eq!(buffer.deadline(), Some(70));
+        assert_eq!(
+            drain(&mut buffer, 70),
+            [Output::Gap { first: 4, count: 1 }, Output::Item(5, 'f')]
+        );
+        assert_eq!(buffer.deadline(), None);
+        assert_eq!(buffer.pop(1000), None);
+    }
+
+    #[test]
+    fn wraps_around() {
+        let mut buffer: ReorderBuffer<u16, u64, u16, 16> = ReorderBuffer::new(10);
+        let mut out = Vec::new();
+        let mut seq = 65_500u16;
+        // otherwise the first insert would set the start
+        buffer.skip_to(seq);
+        for _ in 0..20 {
+            // deliver pairs swapped
+            buffer
+                .insert(seq.wrapping_add(1), seq.wrapping_add(1), 0)
+                .unwrap();
+            buffer.insert(seq, seq, 0).unwrap();
+            while let Some(Output::Item(s, item)) = buffer.pop(0) {
+                assert_eq!(s, item);
+                out.push(item);
+            }
+            seq = seq.wrapping_add(2);
+        }
+        let expected: Vec<u16> = (0..40).map(|k| 65_500u16.wrapping_add(k)).collect();
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn skip_to() {
+        let mut buffer: ReorderBuffer<u32, u64, char, 4> = ReorderBuffer::new(10);
+        buffer.insert(u32::MAX - 1, 'a', 0).unwrap();
+        buffer.insert(u32::MAX, 'b', 0).unwrap();
+        assert_eq!(
+            buffer.insert(10, 'c', 0),
+            Err(InsertError::OutOfWindow('c'))
+        );
+        buffer.skip_to(10);
+        assert!(buffer.is_empty());
+        buffer.insert(10, 'c', 0).unwrap();
+        assert_eq!(buffer.pop(0), Some(Output::Item(10, 'c')));
+        buffer.clear();
+        assert_eq!(buffer.next_expected(), None);
+    }
+}
+
+#[cfg(test)]
+mod replay {
+    use crate::replay::{Replay, ReplayWindow};
+
+    #[test]
+    fn accepts_once() {
+        let mut window: ReplayWindow<u64, 2> = ReplayWindow::new();
+        assert_eq!(window.window(), 64);
+        for seq in [5, 3, 4, 10, 6] {
+            assert_eq!(window.check_and_update(seq), Ok(()));
+        }
+        for seq in [3, 4, 5, 6, 10] {
+            assert_eq!(window.check(seq), Err(Replay::Duplicate));
+            assert_eq!(window.check_and_update(seq), Err(Replay::Duplicate));
+        }
+        assert_eq!(window.check(7), Ok(()));
+        assert_eq!(window.top(), Some(10));
+    }
+
+    #[test]
+    fn too_old() {
+        let mut window: ReplayWindow<u32, 4> = ReplayWindow::with_window(100);
+        window.check_and_update(1000).unwrap();
+        assert_eq!(window.check_and_update(901), Ok(()));
+        assert_eq!(window.check_and_update(900), Err(Replay::TooOld));
+        // jumping ahead forgets everything older than the window
+        window.check_and_update(1150).unwrap();
+        assert_eq!(window.check_and_update(1000), Err(Replay::TooOld));
+        assert_eq!(window.check_and_update(1051), Ok(()));
+        window.check_and_update(100_000).unwrap();
+        for seq in 99_901..100_000 {
+            assert_eq!(window.check(seq), Ok(()), "{}", seq);
+        }
+        window.reset();
+        assert_eq!(window.check_and_update(5), Ok(()));
+        assert_eq!(window.window(), 100);
+    }
+
+    #[test]
+    fn stale_words_are_cleared() {
+        let mut window: ReplayWindow<u64, 3> = ReplayWindow::new();
+        for seq in 0..1000 {
+            window.check_and_update(seq).unwrap();
+        }
+        // every step size, including ones that stay within a word
+        let mut seq = 1000;
+        for step in 1..200 {
+            seq += step;
+            window.check_and_update(seq).unwrap();
+            for age in 1..u64::min(step, 128) {
+                assert_eq!(window.check(seq - age), Ok(()), "{} {}", seq, age);
+            }
+        }
+    }
+
+    #[test]
+    fn wraps_around() {
+        let mut window: ReplayWindow<u16, 2> = ReplayWindow::with_window(32);
+        let start = 65_520u16;
+        for k in 0..40u16 {
+            assert_eq!(window.check_and_update(start.wrapping_add(k)), Ok(()));
+        }
+        assert_eq!(window.top(), Some(23));
+        assert_eq!(window.check_and_update(65_535), Err(Replay::Duplicate));
+        assert_eq!(window.check_and_update(65_520), Err(Replay::TooOld));
+        assert_eq!(window.check_and_update(25), Ok(()));
+        assert_eq!(window.check_and_update(24), Ok(()));
+    }
+}
+
+#[cfg(test)]
+mod arq {
+    use super::Lcg;
+    use crate::arq::{Ack, ReceiveWindow, Received, SendWindow};
+    use std::collections::VecDeque;
+    use std::vec::Vec;
+
+    #[test]
+    fn send_window() {
+        let mut window: SendWindow<u16, u64, char, 3> = SendWindow::new(65_534, 10);
+        assert_eq!(window.send('a', 0), Ok(65_534));
+        assert_eq!(window.send('b', 1), Ok(65_535));
+        assert_eq!(window.send('c', 2), Ok(0));
+        assert_eq!(window.send('d', 2), Err('d'));
+        assert!(window.is_full());
+
+        // acking out of order does not advance the window
+        assert!(window.ack(65_535));
+        assert!(!window.ack(65_535));
+        assert!(!window.ack(1));
+        assert_eq!(window.base(), 65_534);
+        assert!(window.ack(65_534));
+        assert_eq!(window.base(), 0);
+        assert_eq!(window.in_flight(), 1);
+
+        assert_eq!(window.next_timeout(), Some(12));
+        assert_eq!(window.poll_retransmit(11), None);
+        assert_eq!(window.poll_retransmit(12), Some((0, 'c')));
+        assert_eq!(window.poll_retransmit(12), None);
+        assert_eq!(window.next_timeout(), Some(22));
+
+        window.ack_cumulative(1);
+        assert!(window.is_empty());
+        assert_eq!(window.next_timeout(), None);
+        assert_eq!(window.next_seq(), 1);
+    }
+
+    #[test]
+    fn selective_ack() {
+        let mut receiver: ReceiveWindow<u32, char, 8> = ReceiveWindow::new(0);
+        assert_eq!(
+            receiver.ack(),
+            Ack {
+                cumulative: 0,
+                selective: 0
+            }
+        );
+        assert_eq!(receiver.receive(0, 'a'), Received::New);
+        assert_eq!(receiver.receive(2, 'c'), Received::New);
+        assert_eq!(receiver.receive(5, 'f'), Received::New);
+        assert_eq!(receiver.receive(2, 'c'), Received::Duplicate);
+        assert_eq!(receiver.receive(8, 'i'), Received::OutOfWindow);
+        assert_eq!(
+            receiver.ack(),
+            Ack {
+                cumulative: 1,
+                selective: 0b1001
+            }
+        );
+        assert_eq!(receiver.pop(), Some((0, 'a')));
+        assert_eq!(receiver.pop(), None);
+        assert_eq!(receiver.receive(0, 'a'), Received::Duplicate);
+        assert_eq!(receiver.len(), 2);
+
+        let mut sender: SendWindow<u32, u64, char, 8> = SendWindow::new(0, 10);
+        for c in "abcdef".chars() {
+            sender.send(c, 0).unwrap();
+        }
+        sender.apply(receiver.ack());
+        assert_eq!(sender.base(), 1);
+        assert_eq!(sender.poll_retransmit(10), Some((1, 'b')));
+        assert_eq!(sender.poll_retransmit(10), Some((3, 'd')));
+        assert_eq!(sender.poll_retransmit(10), Some((4, 'e')));
+        assert_eq!(sender.poll_retransmit(10), None);
+    }
+
+    // deterministic link that drops about a third of packets and delays the
+    // rest by one to four ticks, so they also arrive out of order
+    struct LossyLink<P> {
+        random: Lcg,
+        in_transit: Vec<(u64, P)>,
+    }
+
+    impl<P> LossyLink<P> {
+        fn new(seed: u64) -> Self {
+            LossyLink {
+                random: Lcg::new(seed),
+                in_transit: Vec::new(),
+            }
+        }
+
+        fn send(&mut self, packet: P, now: u64) {
+            if self.random.below(3) != 0 {
+                let delay = 1 + self.random.below(4);
+                self.in_transit.push((now + delay, packet));
+            }
+        }
+
+        fn deliver(&mut self, now: u64) -> Vec<P> {
+            let (due, rest) = self.in_transit.drain(..).partition(|(at, _)| *at <= now);
+            self.in_transit = rest;
+            due.into_iter().map(|(_, packet)| packet).collect()
+        }
+    }
+
+    #[test]
+    fn lossy_link() {
+        for seed in 0..4 {
+            let mut sender: SendWindow<u8, u64, u32, 16> = SendWindow::new(250, 8);
+            let mut receiver: ReceiveWindow<u8, u32, 16> = ReceiveWindow::new(250);
+            let mut data = LossyLink::new(seed);
+            let mut acks = LossyLink::new(seed + 100);
+            let mut queue: VecDeque<u32> = (0..1000).collect();
+            let mut delivered = Vec::new();
+
+            let mut now = 0;
+            while delivered.len() < 1000 {
+                assert!(now < 100_000, "no progress");
+                while let Some(&payload) = queue.front() {
+                    let Ok(seq) = sender.send(payload, now) else {
+                        break;
+                    };
+                    queue.pop_front();
+                    data.send((seq, payload), now);
+                }
+                while let Some(packet) = sender.poll_retransmit(now) {
+                    data.send(packet, now);
+                }
+                for (seq, payload) in data.deliver(now) {
+                    receiver.receive(seq, payload);
+                    acks.send(receiver.ack(), now);
+                }
+                while let Some((_, payload)) = receiver.pop() {
+                    delivered.push(payload);
+                }
+                for ack in acks.deliver(now) {
+                    sender.apply(ack);
+                }
+                now += 1;
+            }
+            assert_eq!(delivered, (0..1000).collect::<Vec<_>>());
+            assert!(receiver.is_empty());
+        }
+    }
+}
+
+#[cfg(test)]
+mod undo {
+    use crate::undo::UndoHistory;
+
+    #[test]
+    fn undo_redo() {
+        let mut history: UndoHistory<u32, 8> = UndoHistory::new(0);
+        assert_eq!(history.undo(), None);
+        assert_eq!(history.redo(), None);
+        for state in 1..=3 {
+            history.record(state);
+        }
+        assert_eq!(history.undo(), Some(2));
+        assert_eq!(history.undo(), Some(1));
+        assert_eq!(history.redo(), Some(2));
+        assert_eq!(history.current(), 2);
+        assert_eq!((history.undo_len(), history.redo_len()), (2, 1));
+        assert_eq!(history.undo(), Some(1));
+        assert_eq!(history.undo(), Some(0));
+        assert_eq!(history.undo(), None);
+        assert_eq!(history.redo_len(), 3);
+    }
+
+    #[test]
+    fn record_truncates_redo() {
+        let mut history: UndoHistory<u32, 8> = UndoHistory::new(0);
+        for state in 1..=5 {
+            history.record(state);
+        }
+        history.undo();
+        history.undo();
+        history.record(10);
+        assert!(!history.can_redo());
+        assert_eq!(history.current(), 10);
+        assert_eq!(history.undo(), Some(3));
+        assert_eq!(history.redo(), Some(10));
+        assert_eq!(history.redo(), None);
+        assert_eq!(history.undo_len(), 4);
+    }
+
+    #[test]
+    fn drops_oldest() {
+        let mut history: UndoHistory<u32, 4> = UndoHistory::new(0);
+        for state in 1..=10 {
+            history.record(state);
+        }
+        assert_eq!(history.undo_len(), 3);
+        assert_eq!(history.undo(), Some(9));
+        assert_eq!(history.undo(), Some(8));
+        assert_eq!(history.undo(), Some(7));
+        assert_eq!(history.undo(), None);
+        // a full ring with redo states left
+        history.redo();
+        history.record(20);
+        assert_eq!(history.undo(), Some(8));
+        assert_eq!(history.undo(), Some(7));
+        assert_eq!(history.undo(), None);
+
+        history.clear(5);
+        assert_eq!(
+            (history.current(), history.undo_len(), history.redo_len()),
+            (5, 0, 0)
+        );
+    }
+
+    #[test]
+    fn coalescing() {
+        // (word, length) merges while the same word grows by a letter
+        type Text = (u8, u8);
+        fn same_word(current: &Text, new: &Text) -> bool {
+            current.0 == new.0 && current.1 + 1 == new.1
+        }
+        let mut history: UndoHistory<Text, 8> = UndoHistory::with_coalescing((0, 0), same_word);
+        // the first record never merges into the initial state
+        history.record((0, 1));
+        history.record((0, 2));
+        history.record((0, 3));
+        history.record((1, 1));
+        history.record((1, 2));
+        assert_eq!(history.undo_len(), 2);
+        assert_eq!(history.undo(), Some((0, 3)));
+        assert_eq!(history.undo(), Some((0, 0)));
+        history.redo();
+        history.record((0, 4));
+        history.checkpoint();
+        history.record((0, 5));
+        assert_eq!(history.undo(), Some((0, 4)));
+        assert_eq!(history.undo(), Some((0, 3)));
+
+        history.clear((2, 0));
+        history.record((2, 1));
+        history.record((2, 2));
+        assert_eq!(history.undo(), Some((2, 0)));
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod history {
+    use crate::history::{Duplicates, History, Match, Policy};
+    use std::vec::Vec;
+
+    fn entries<const N: usize>(history: &History<N>) -> Vec<&str> {
+        history.iter().collect()
+    }
+
+    #[test]
+    fn keeps_last_n() {
+        let mut history: History<3> = History::new();
+        for line in ["ls", "", "  ", "cd /", "ls", "make"] {
+            history.add(line);
+        }
+        assert_eq!(entries(&history), ["cd /", "ls", "make"]);
+        assert_eq!(history.get(0), Some("make"));
+        assert_eq!(history.get(3), None);
+        history.clear();
+        assert!(history.is_empty());
+    }
+
+    #[test]
+    fn policies() {
+        let mut history: History<8> = History::with_policy(Policy {
+            duplicates: Duplicates::IgnoreConsecutive,
+            ignore_space: true,
+        });
+        assert!(history.add("ls"));
+        assert!(!history.add("ls"));
+        assert!(!history.add(" secret"));
+        assert!(history.add("cd"));
+        assert!(history.add("ls"));
+        assert_eq!(entries(&history), ["ls", "cd", "ls"]);
+
+        let mut history: History<4> = History::with_policy(Policy {
+            duplicates: Duplicates::EraseOlder,
+            ignore_space: false,
+        });
+        for line in ["a", "b", "a", "c", "a", "d"] {
+            history.add(line);
+        }
+        assert_eq!(entries(&history), ["b", "c", "a", "d"]);
+        // wrapped storage
+        history.add("b");
+        history.add("e");
+        assert_eq!(entries(&history), ["a", "d", "b", "e"]);
+    }
+
+    #[test]
+    fn navigation() {
+        let mut history: History<4> = History::new();
+        for line in ["one", "two", "three"] {
+            history.add(line);
+        }
+        assert_eq!(history.next(), None);
+        assert_eq!(history.prev("draft"), Some("three"));
+        assert_eq!(history.prev("three"), Some("two"));
+        assert_eq!(history.prev("two"), Some("one"));
+        assert_eq!(history.prev("one"), None);
+        assert_eq!(history.cursor(), Some(2));
+        assert_eq!(history.next(), Some("two"));
+        assert_eq!(history.next(), Some("three"));
+        assert_eq!(history.next(), Some("draft"));
+        assert_eq!(history.next(), None);
+
+        history.prev("x");
+        history.add("four");
+        assert_eq!(history.cursor(), None);
+        assert_eq!(history.prev(""), Some("four"));
+        history.reset_navigation();
+        assert_eq!(history.prev(""), Some("four"));
+    }
+
+    #[test]
+    fn search() {
+        let mut history: History<8> = History::new();
+        for line in [
+            "git status",
+            "cargo test",
+            "git commit",
+            "cargo build",
+            "git commit",
+        ] {
+            history.add(line);
+        }
+        assert_eq!(
+            history.search_backward("git", Match::Prefix, "g"),
+            Some("git commit")
+        );
+        // the older duplicate is skipped
+        assert_eq!(
+            history.search_backward("git", Match::Prefix, "g"),
+            Some("git status")
+        );
+        assert_eq!(history.search_backward("git", Match::Prefix, "g"), None);
+        assert_eq!(history.cursor(), Some(4));
+        assert_eq!(
+            history.search_forward("cargo", Match::Prefix),
+            Some("cargo test")
+        );
+        assert_eq!(
+            history.search_forward("build", Match::Substring),
+            Some("cargo build")
+        );
+        assert_eq!(history.search_forward("test", Match::Substring), None);
+        assert_eq!(history.next(), Some("git commit"));
+        assert_eq!(history.next(), Some("g"));
+
+        assert_eq!(history.search_backward("commit", Match::Prefix, ""), None);
+        assert_eq!(history.cursor(), None);
+    }
+}
+
+#[cfg(test)]
+mod scrollback {
+    use super::Lcg;
+    use crate::scrollback::Scrollback;
+    use core::fmt::Write;
+    use std::string::{String, ToString};
+    use std::vec::Vec;
+
+    fn lines<const B: usize, const L: usize>(scrollback: &Scrollback<B, L>) -> Vec<String> {
+        scrollback.lines().map(|line| line.to_string()).collect()
+    }
+
+    #[test]
+    fn splits_lines() {
+        let mut scrollback: Scrollback<64, 8> = Scrollback::new();
+        assert!(scrollback.is_empty());
+        scrollback.write(b"one\ntw");
+        assert!(scrollback.has_partial());
+        assert_eq!(lines(&scrollback), ["one", "tw"]);
+        scrollback.write(b"o\r\n\nthree\n");
+        assert!(!scrollback.has_partial());
+        assert_eq!(lines(&scrollback), ["one", "two", "", "three"]);
+        assert_eq!(scrollback.bytes(), 12);
+        let tail: Vec<_> = scrollback.tail(2).map(|line| line.to_string()).collect();
+        assert_eq!(tail, ["", "three"]);
+        assert_eq!(scrollback.tail(10).len(), 4);
+        assert_eq!(
+            scrollback.lines().next_back().unwrap().to_str(),
+            Ok("three")
+        );
+        assert_eq!(scrollback.get(3).unwrap().as_bytes(), b"one");
+        scrollback.clear();
+        assert_eq!(scrollback.lines().count(), 0);
+    }
+
+    #[test]
+    fn invalid_utf8() {
+        let mut scrollback: Scrollback<64, 8> = Scrollback::new();
+        // a character split across writes, then a stray continuation byte
+        let snowman = "☃".as_bytes();
+        scrollback.write(&snowman[..1]);
+        scrollback.write(&snowman[1..]);
+        scrollback.write(b"\x80!\n");
+        let line = scrollback.get(0).unwrap();
+        assert!(line.to_str().is_err());
+        assert_eq!(line.to_string(), "☃\u{FFFD}!");
+    }
+
+    #[test]
+    fn limits() {
+        let mut scrollback: Scrollback<16, 3> = Scrollback::new();
+        writeln!(scrollback, "a").unwrap();
+        writeln!(scrollback, "b").unwrap();
+        writeln!(scrollback, "c").unwrap();
+        writeln!(scrollback, "d").unwrap();
+        assert_eq!(lines(&scrollback), ["b", "c", "d"]);
+        // a long line takes the whole budget, and then some
+        write!(scrollback, "{}", "x".repeat(20)).unwrap();
+        assert_eq!(lines(&scrollback), ["x".repeat(16)]);
+        writeln!(scrollback, "\nshort").unwrap();
+        assert_eq!(lines(&scrollback), ["short"]);
+        writeln!(scrollback, "0123456789").unwrap();
+        assert_eq!(lines(&scrollback), ["short", "0123456789"]);
+        // lines are contiguous, so space lost at the end of the ring may
+        // evict more than the byte count alone would
+        writeln!(scrollback, "abcdef").unwrap();
+        assert_eq!(lines(&scrollback).last().unwrap(), "abcdef");
+        assert!(!lines(&scrollback).contains(&"short".to_string()));
+    }
+
+    #[test]
+    fn matches_model() {
+        let mut lcg = Lcg::new(7);
+        let mut random = move |n: u64| lcg.below(n);
+        let mut scrollback: Scrollback<40, 6> = Scrollback::new();
+        let mut model: Vec<Vec<u8>> = Vec::new();
+        let mut partial = false;
+        for _ in 0..5000 {
+            let chunk: Vec<u8> = (0..random(12))
+                .map(|_| match random(6) {
+                    0 => b'\n',
+                    _ => b'a' + random(26) as u8,
+                })
+                .collect();
+            scrollback.write(&chunk);
+            for &byte in &chunk {
+                if byte == b'\n' {
+                    if !partial {
+                        model.push(Vec::new());
+                    }
+                    partial = false;
+                } else {
+                    if !partial {
+                        model.push(Vec::new());
+                        partial = true;
+                    }
+                    let line = model.last_mut().unwrap();
+                    if line.len() < 40 {
+                        line.push(byte);
+                    }
+                }
+            }
+            assert!(scrollback.len() <= 6);
+            assert!(scrollback.bytes() <= 40);
+            assert_eq!(scrollback.has_partial(), partial);
+            let stored: Vec<&[u8]> = scrollback.lines().map(|line| line.as_bytes()).collect();
+            let expected: Vec<&[u8]> = model[model.len() - stored.len()..]
+                .iter()
+                .map(|line| line.as_slice())
+                .collect();
+            assert_eq!(stored, expected);
+            if !model.is_empty() {
+                assert!(!stored.is_empty());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod recorder {
+    use crate::recorder::{FlightRecorder, State};
+    use std::vec::Vec;
+
+    #[test]
+    fn pre_and_post_trigger() {
+        let mut recorder: FlightRecorder<u32, 8> = FlightRecorder::new(0, 3);
+        assert!(!recorder.trigger());
+        recorder.arm();
+        for event in 1..=20 {
+            recorder.put(event);
+        }
+        assert!(recorder.capture().is_none());
+        assert!(recorder.trigger());
+        assert!(!recorder.trigger());
+        recorder.put(21);
+        assert_eq!(recorder.state(), State::Triggered { remaining: 2 });
+        for event in 22..=30 {
+            recorder.put(event);
+        }
+        assert!(recorder.is_frozen());
+        assert_eq!(recorder.dropped(), 7);
+
+        let capture = recorder.capture().unwrap();
+        assert_eq!(capture.len(), 8);
+        assert_eq!(capture.trigger_index(), 5);
+        assert_eq!(capture.get(5), Some(21));
+        assert_eq!(
+            capture.iter().collect::<Vec<_>>(),
+            (16..=23).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            capture.pre_trigger().collect::<Vec<_>>(),
+            [16, 17, 18, 19, 20]
+        );
+        assert_eq!(
+            capture.post_trigger().rev().collect::<Vec<_>>(),
+            [23, 22, 21]
+        );
+    }
+
+    #[test]
+    fn short_history_and_rearm() {
+        let mut recorder: FlightRecorder<u32, 8> = FlightRecorder::new(0, 2);
+        recorder.arm();
+        assert!(recorder.put_and_trigger(1));
+        recorder.put(2);
+        recorder.put(3);
+        let capture = recorder.capture().unwrap();
+        assert_eq!(capture.iter().collect::<Vec<_>>(), [1, 2, 3]);
+        assert_eq!(capture.trigger_index(), 1);
+
+        recorder.rearm();
+        assert_eq!(recorder.state(), State::Armed);
+        assert!(recorder.is_empty());
+        recorder.put(10);
+        recorder.disarm();
+        assert!(!recorder.trigger());
+        assert_eq!(recorder.iter().collect::<Vec<_>>(), [10]);
+    }
+
+    #[test]
+    fn no_post_trigger() {
+        let mut recorder: FlightRecorder<u32, 4> = FlightRecorder::new(0, 0);
+        recorder.arm();
+        for event in 0..6 {
+            recorder.put(event);
+        }
+        recorder.trigger();
+        recorder.put(6);
+        let capture = recorder.capture().unwrap();
+        assert_eq!(capture.iter().collect::<Vec<_>>(), [2, 3, 4, 5]);
+        assert_eq!(capture.post_trigger().count(), 0);
+    }
+}
+
+#[cfg(test)]
+mod trigger {
+    use crate::trigger::{Condition, EdgeTrigger, Slope};
+    use std::vec::Vec;
+
+    fn windows<const N: usize>(trigger: &mut EdgeTrigger<f64, N>, signal: &[f64]) -> Vec<[f64; N]> {
+        let mut windows = Vec::new();
+        trigger.process(signal, |window| windows.push(*window));
+        windows
+    }
+
+    // a ramp 0..10 repeated, so the sample value tells its phase
+    fn sawtooth(periods: usize) -> Vec<f64> {
+        (0..periods * 10).map(|i| (i % 10) as f64).collect()
+    }
+
+    #[test]
+    fn rising_edge_window() {
+        let condition = Condition::Edge {
+            slope: Slope::Rising,
+            level: 5.0,
+        };
+        let mut trigger: EdgeTrigger<f64, 6> = EdgeTrigger::new(condition, 1.0, 2);
+        let windows = windows(&mut trigger, &sawtooth(3));
+        assert_eq!(windows.len(), 3);
+        for window in &windows {
+            assert_eq!(window, &[3.0, 4.0, 5.0, 6.0, 7.0, 8.0]);
+        }
+    }
+
+    #[test]
+    fn falling_edge() {
+        let condition = Condition::Edge {
+            slope: Slope::Falling,
+            level: 5.0,
+        };
+        let mut trigger: EdgeTrigger<f64, 4> = EdgeTrigger::new(condition, 1.0, 3);
+        let windows = windows(&mut trigger, &sawtooth(2));
+        // the drop from 9 to 0 is the only falling edge per period
+        assert_eq!(windows, [[7.0, 8.0, 9.0, 0.0]]);
+        trigger.put(9.0);
+        assert!(trigger.put(0.0).is_some());
+    }
+
+    #[test]
+    fn hysteresis_rejects_noise() {
+        let condition = Condition::Edge {
+            slope: Slope::Either,
+            level: 0.0,
+        };
+        let mut trigger: EdgeTrigger<f64, 2> = EdgeTrigger::new(condition, 1.0, 0);
+        // wiggles within the band never switch, full swings do
+        let signal = [-1.0, 0.2, -0.2, 0.3, 0.6, 0.1, -0.4, -0.6, 0.0];
+        let windows = windows(&mut trigger, &signal);
+        assert_eq!(windows, [[0.6, 0.1], [-0.6, 0.0]]);
+    }
+
+    #[test]
+    fn level() {
+        let condition = Condition::Level {
+            above: false,
+            level: -2.0,
+        };
+        let mut trigger: EdgeTrigger<f64, 3> = EdgeTrigger::new(condition, 0.0, 1);
+        let signal = [0.0, 0.0, -3.0, -4.0, -5.0, -6.0, -7.0, 0.0];
+        let windows = windows(&mut trigger, &signal);
+        // retriggers right away once position samples came in
+        assert_eq!(windows, [[0.0, -3.0, -4.0], [-5.0, -6.0, -7.0]]);
+        assert!(!trigger.is_triggered());
+    }
+
+    #[test]
+    fn pulse_width() {
+        let condition = Condition::PulseWidth {
+            positive: true,
+            level: 0.5,
+            min: 2,
+            max: 3,
+        };
+        let mut trigger: EdgeTrigger<f64, 5> = EdgeTrigger::new(condition, 0.2, 4);
+        let mut signal = Vec::new();
+        for width in [1, 2, 4, 3] {
+            signal.extend_from_slice(&[0.0; 3]);
+            signal.extend(core::iter::repeat_n(1.0, width));
+        }
+        signal.push(0.0);
+        let windows = windows(&mut trigger, &signal);
+        // the window ends on the sample closing the pulse
+        assert_eq!(
+            windows,
+            [[0.0, 0.0, 1.0, 1.0, 0.0], [0.0, 1.0, 1.0, 1.0, 0.0]]
+        );
+    }
+}
+
+#[cfg(test)]
+mod pyramid {
+    use super::Lcg;
+    use crate::pyramid::MinMaxPyramid;
+    use std::vec::Vec;
+
+    fn samples(len: usize) -> Vec<i32> {
+        let mut random = Lcg::new(11);
+        (0..len).map(|_| random.below(2001) as i32 - 1000).collect()
+    }
+
+    fn exact(samples: &[i32]) -> Option<(i32, i32)> {
+        let min = samples.iter().min()?;
+        let max = samples.iter().max()?;
+        Some((*min, *max))
+    }
+
+    #[test]
+    fn levels() {
+        let mut pyramid: MinMaxPyramid<i32, 4, 3> = MinMaxPyramid::new(0);
+        pyramid.put_slice(&[5, 1, 7, 3, 2, 8, 6]);
+        assert_eq!(pyramid.count(), 7);
+        assert_eq!(pyramid.bucket(0, 6), Some((6, 6)));
+        assert_eq!(pyramid.bucket(0, 2), None);
+        assert_eq!(pyramid.bucket(1, 0), Some((1, 5)));
+        assert_eq!(pyramid.bucket(1, 2), Some((2, 8)));
+        assert_eq!(pyramid.bucket(1, 3), Some((6, 6)));
+        assert_eq!(pyramid.bucket(2, 0), Some((1, 7)));
+        assert_eq!(pyramid.bucket(2, 1), Some((2, 8)));
+        assert_eq!(pyramid.bucket(2, 2), None);
+        assert_eq!(pyramid.first_sample(0), 3);
+        assert_eq!(pyramid.first_sample(2), 0);
+        pyramid.clear();
+        assert_eq!(pyramid.bucket(2, 0), None);
+    }
+
+    #[test]
+    fn empty() {
+        let pyramid: MinMaxPyramid<i32, 4, 3> = MinMaxPyramid::new(0);
+        let mut out = [Some((1, 1)); 4];
+        pyramid.query_last(10, &mut out);
+        assert_eq!(out, [None; 4]);
+        pyramid.query(5..20, &mut out);
+        assert_eq!(out, [None; 4]);
+        assert_eq!(pyramid.bucket(2, 0), None);
+    }
+
+    #[test]
+    fn aligned_queries_are_exact() {
+        let data = samples(1003);
+        let mut pyramid: MinMaxPyramid<i32, 16, 6> = MinMaxPyramid::new(0);
+        pyramid.put_slice(&data);
+
+        let mut out = [None; 16];
+        pyramid.query(480..992, &mut out);
+        for (p, pixel) in out.iter().enumerate() {
+            let start = 480 + 32 * p;
+            assert_eq!(*pixel, exact(&data[start..start + 32]));
+        }
+        // the newest, incomplete buckets
+        let mut out = [None; 1];
+        pyramid.query_last(3, &mut out);
+        assert_eq!(out[0], exact(&data[1000..]));
+        pyramid.query_last(11, &mut out);
+        assert_eq!(out[0], exact(&data[992..]));
+    }
+
+    #[test]
+    fn queries_cover_range() {
+        let data = samples(5000);
+        let mut pyramid: MinMaxPyramid<i32, 32, 8> = MinMaxPyramid::new(0);
+        pyramid.put_slice(&data);
+        let count = data.len() as u64;
+        for (start, end, pixels) in [
+            (4000, 5000, 100),
+            (4990, 5000, 40),
+            (1000, 4500, 7),
+            (4800, 5200, 50),
+            (0, 5000, 300),
+        ] {
+            let mut out = std::vec![None; pixels];
+            pyramid.query(start..end, &mut out);
+            for (p, pixel) in out.iter().enumerate() {
+                let s = start + (end - start) * p as u64 / pixels as u64;
+                let e = start + (end - start) * (p as u64 + 1) / pixels as u64;
+                let e = u64::max(e, s + 1).min(count);
+                if s >= count {
+                    assert_eq!(*pixel, None);
+                    continue;
+                }
+                let Some((min, max)) = *pixel else {
+                    // only when that part of history is gone
+                    assert!(s < pyramid.first_sample(7));
+                    continue;
+                };
+                let (true_min, true_max) = exact(&data[s as usize..e as usize]).unwrap();
+                if s >= pyramid.first_sample(7) {
+                    assert!(min <= true_min && max >= true_max);
+                }
+                assert!(min <= max);
+            }
+        }
+    }
+}
@@ -0,0 +1,278 @@
+use crate::RingBuffer;
+use core::time::Duration;
+
+// Any monotonic, totally ordered point in time: tick counters, durations
+// since boot, ...
+pub trait Timestamp: Copy + Ord + Default {
+    fn saturating_sub(self, rhs: Self) -> Self;
+    fn saturating_add(self, rhs: Self) -> Self;
+    // number of the smallest representable units since zero
+    fn ticks(self) -> u128;
+    // saturates at the largest representable value
+    fn from_ticks(ticks: u128) -> Self;
+}
+
+macro_rules! impl_timestamp {
+    ( $( $t:ty ),* ) => {
+        $(
+            impl Timestamp for $t {
+                #[inline(always)]
+                fn saturating_sub(self, rhs: Self) -> Self {
+                    <$t>::saturating_sub(self, rhs)
+                }
+
+                #[inline(always)]
+                fn saturating_add(self, rhs: Self) -> Self {
+                    <$t>::saturating_add(self, rhs)
+                }
+
+                #[inline(always)]
+                fn ticks(self) -> u128 {
+                    self as u128
+                }
+
+                #[inline(always)]
+                fn from_ticks(ticks: u128) -> Self {
+                    u128::min(ticks, <$t>::MAX as u128) as $t
+                }
+            }
+        )*
+    };
+}
+
+impl_timestamp!(u8, u16, u32, u64, u128, usize);
+
+impl Timestamp for Duration {
+    fn saturating_sub(self, rhs: Self) -> Self {
+        Duration::saturating_sub(self, rhs)
+    }
+
+    fn saturating_add(self, rhs: Self) -> Self {
+        Duration::saturating_add(self, rhs)
+    }
+
+    fn ticks(self) -> u128 {
+        self.as_nanos()
+    }
+
+    fn from_ticks(ticks: u128) -> Self {
+        const NANOS_PER_SEC: u128 = 1_000_000_000;
+        match u64::try_from(ticks / NANOS_PER_SEC) {
+            Ok(secs) => Duration::new(secs, (ticks % NANOS_PER_SEC) as u32),
+            Err(_) => Duration::MAX,
+        }
+    }
+}
+
+pub trait Clock {
+    type Timestamp: Timestamp;
+    fn now(&self) -> Self::Timestamp;
+}
+
+impl<F, Ts> Clock for F
+where
+    F: Fn() -> Ts,
+    Ts: Timestamp,
+{
+    type Timestamp = Ts;
+    fn now(&self) -> Ts {
+        self()
+    }
+}
+
+// time elapsed since the clock was created
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy)]
+pub struct StdClock {
+    start: std::time::Instant,
+}
+
+#[cfg(feature = "std")]
+impl StdClock {
+    pub fn new() -> Self {
+        StdClock {
+            start: std::time::Instant::now(),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Default for StdClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "std")]
+impl Clock for StdClock {
+    type Timestamp = Duration;
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+// Timestamps must be put in non-decreasing order, which keeps the entries
+// sorted and allows binary searching them.
+#[derive(Debug, Clone)]
+pub struct TimedRingBuffer<Ts, T, const N: usize>
+where
+    Ts: Timestamp,
+    T: Copy,
+{
+    ring: RingBuffer<(Ts, T), N>,
+    len: usize,
+}
+
+impl<Ts, T, const N: usize> Default for TimedRingBuffer<Ts, T, N>
+where
+    Ts: Timestamp,
+    T: Default + Copy,
+{
+    fn default() -> Self {
+        TimedRingBuffer {
+            ring: RingBuffer::default(),
+            len: 0,
+        }
+    }
+}
+
+impl<Ts, T, const N: usize> TimedRingBuffer<Ts, T, N>
+where
+    Ts: Timestamp,
+    T: Copy,
+{
+    pub fn new(init_value: T) -> Self {
+        TimedRingBuffer {
+            ring: RingBuffer::new((Ts::default(), init_value)),
+            len: 0,
+        }
+    }
+
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    pub fn put(&mut self, timestamp: Ts, item: T) {
+        if N == 0 {
+            return;
+        }
+        debug_assert!(
+            self.is_empty() || self.ring.get_newest(0).0 <= timestamp,
+            "timestamps must not decrease"
+        );
+        self.ring.put((timestamp, item));
+        self.len = usize::min(self.len + 1, N);
+    }
+
+    pub fn put_now<C>(&mut self, clock: &C, item: T)
+    where
+        C: Clock<Timestamp = Ts>,
+    {
+        self.put(clock.now(), item)
+    }
+
+    #[inline(always)]
+    fn entry(&self, idx: usize) -> (Ts, T) {
+        self.ring.get_oldest(N - self.len + idx)
+    }
+
+    pub fn get_oldest(&self, idx: usize) -> Option<(Ts, T)> {
+        (idx < self.len).then(|| self.entry(idx))
+    }
+
+    pub fn get_newest(&self, idx: usize) -> Option<(Ts, T)> {
+        (idx < self.len).then(|| self.entry(self.len - 1 - idx))
+    }
+
+    pub fn newest_timestamp(&self) -> Option<Ts> {
+        self.get_newest(0).map(|(ts, _)| ts)
+    }
+
+    pub fn oldest_timestamp(&self) -> Option<Ts> {
+        self.get_oldest(0).map(|(ts, _)| ts)
+    }
+
+    // oldest first
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (Ts, T)> + ExactSizeIterator + '_ {
+        (0..self.len).map(|i| self.entry(i))
+    }
+
+    // same contract as slice::partition_point, applied to the timestamps
+    pub fn partition_point<P>(&self, mut pred: P) -> usize
+    where
+        P: FnMut(Ts) -> bool,
+    {
+        let (mut lo, mut hi) = (0, self.len);
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            if pred(self.entry(mid).0) {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+        lo
+    }
+
+    // same contract as slice::binary_search, indices count from the oldest entry
+    pub fn binary_search(&self, t: Ts) -> Result<usize, usize> {
+        let idx = self.partition_point(|ts| ts < t);
+        match self.get_oldest(idx) {
+            Some((ts, _)) if ts == t => Ok(idx),
+            _ => Err(idx),
+        }
+    }
+
+    // newest entry with a timestamp <= t
+    pub fn latest_at(&self, t: Ts) -> Option<(Ts, T)> {
+        let idx = self.partition_point(|ts| ts <= t);
+        idx.checked_sub(1).map(|i| self.entry(i))
+    }
+
+    // entries with a timestamp >= t, oldest first
+    pub fn iter_since(
+        &self,
+        t: Ts,
+    ) -> impl DoubleEndedIterator<Item = (Ts, T)> + ExactSizeIterator + '_ {
+        (self.partition_point(|ts| ts < t)..self.len).map(|i| self.entry(i))
+    }
+
+    pub fn count_since(&self, t: Ts) -> usize {
+        self.len - self.partition_point(|ts| ts < t)
+    }
+
+    // entries no older than span before now
+    pub fn iter_within(
+        &self,
+        now: Ts,
+        span: Ts,
+    ) -> impl DoubleEndedIterator<Item = (Ts, T)> + ExactSizeIterator + '_ {
+        self.iter_since(now.saturating_sub(span))
+    }
+
+    // drops up to count of the oldest entries, returns how many were dropped
+    pub fn evict_oldest(&mut self, count: usize) -> usize {
+        let evicted = usize::min(count, self.len);
+        self.len -= evicted;
+        evicted
+    }
+
+    // drops every entry with a timestamp < t, returns how many were dropped
+    pub fn evict_older_than(&mut self, t: Ts) -> usize {
+        let evicted = self.partition_point(|ts| ts < t);
+        self.len -= evicted;
+        evicted
+    }
+}
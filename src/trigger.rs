@@ -0,0 +1,210 @@
+use crate::float::Float;
+use crate::RingBuffer;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Slope {
+    Rising,
+    Falling,
+    Either,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Condition<F> {
+    // crossing level in the direction of slope
+    Edge {
+        slope: Slope,
+        level: F,
+    },
+    // any sample at or above (or at or below) level
+    Level {
+        above: bool,
+        level: F,
+    },
+    // fires at the end of a pulse lasting min to max samples; positive
+    // pulses are above level
+    PulseWidth {
+        positive: bool,
+        level: F,
+        min: usize,
+        max: usize,
+    },
+}
+
+// Evaluates a trigger condition on every sample and returns the N samples
+// around each trigger, with the triggering sample at index position. A
+// trigger is only accepted once position samples have come in since the
+// previous window, and not while the samples after it are being collected.
+//
+// Edges use a Schmitt trigger so noise smaller than hysteresis does not
+// retrigger: a rising edge fires at level and re-arms below
+// level - hysteresis, a falling edge the other way round, and Either and
+// pulse widths switch at level +- hysteresis / 2.
+#[derive(Debug, Clone)]
+pub struct EdgeTrigger<F, const N: usize>
+where
+    F: Float,
+{
+    history: RingBuffer<F, N>,
+    frame: [F; N],
+    condition: Condition<F>,
+    hysteresis: F,
+    position: usize,
+    high: Option<bool>,
+    // samples since the pulse started, if in one
+    pulse: Option<usize>,
+    since_window: usize,
+    // samples still needed after the trigger
+    remaining: Option<usize>,
+}
+
+impl<F, const N: usize> EdgeTrigger<F, N>
+where
+    F: Float,
+{
+    pub fn new(condition: Condition<F>, hysteresis: F, position: usize) -> Self {
+        assert!(position < N, "position must be less than N");
+        EdgeTrigger {
+            history: RingBuffer::new(F::ZERO),
+            frame: [F::ZERO; N],
+            condition,
+            hysteresis,
+            position,
+            high: None,
+            pulse: None,
+            since_window: 0,
+            remaining: None,
+        }
+    }
+
+    pub fn put(&mut self, x: F) -> Option<&[F; N]> {
+        self.history.put(x);
+        self.since_window += 1;
+        let fired = self.detect(x);
+        let remaining = match self.remaining {
+            Some(remaining) => remaining - 1,
+            None if fired && self.since_window > self.position => N - self.position - 1,
+            None => return None,
+        };
+        if remaining != 0 {
+            self.remaining = Some(remaining);
+            return None;
+        }
+        self.remaining = None;
+        self.since_window = 0;
+        let (older, newer) = self.history.as_slices();
+        for (y, x) in self.frame.iter_mut().zip(older.iter().chain(newer)) {
+            *y = *x;
+        }
+        Some(&self.frame)
+    }
+
+    // feeds a block of any size, calling on_window for every completed window
+    pub fn process<C>(&mut self, block: &[F], mut on_window: C)
+    where
+        C: FnMut(&[F; N]),
+    {
+        for &x in block {
+            if let Some(window) = self.put(x) {
+                on_window(window);
+            }
+        }
+    }
+
+    // whether a trigger has fired and its window is being filled
+    pub fn is_triggered(&self) -> bool {
+        self.remaining.is_some()
+    }
+
+    pub fn condition(&self) -> Condition<F> {
+        self.condition
+    }
+
+    pub fn set_condition(&mut self, condition: Condition<F>) {
+        self.condition = condition;
+        self.high = None;
+        self.pulse = None;
+    }
+
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn reset(&mut self) {
+        self.history = RingBuffer::new(F::ZERO);
+        self.high = None;
+        self.pulse = None;
+        self.since_window = 0;
+        self.remaining = None;
+    }
+
+    fn detect(&mut self, x: F) -> bool {
+        let half = self.hysteresis / F::from_f64(2.0);
+        match self.condition {
+            Condition::Edge { slope, level } => {
+                let (low, high) = match slope {
+                    Slope::Rising => (level - self.hysteresis, level),
+                    Slope::Falling => (level, level + self.hysteresis),
+                    Slope::Either => (level - half, level + half),
+                };
+                match self.schmitt(x, low, high) {
+                    Some(rising) => match slope {
+                        Slope::Rising => rising,
+                        Slope::Falling => !rising,
+                        Slope::Either => true,
+                    },
+                    None => false,
+                }
+            }
+            Condition::Level { above, level } => {
+                if above {
+                    x >= level
+                } else {
+                    x <= level
+                }
+            }
+            Condition::PulseWidth {
+                positive,
+                level,
+                min,
+                max,
+            } => {
+                let edge = self.schmitt(x, level - half, level + half);
+                match (edge, self.pulse) {
+                    (Some(rising), _) if rising == positive => {
+                        self.pulse = Some(1);
+                        false
+                    }
+                    (Some(_), Some(width)) => {
+                        self.pulse = None;
+                        (min..=max).contains(&width)
+                    }
+                    (None, Some(width)) => {
+                        self.pulse = Some(width + 1);
+                        false
+                    }
+                    _ => false,
+                }
+            }
+        }
+    }
+
+    // Updates the Schmitt state, returning Some(true) on a rising and
+    // Some(false) on a falling transition.
+    fn schmitt(&mut self, x: F, low: F, high: F) -> Option<bool> {
+        match self.high {
+            None => {
+                self.high = Some(x >= high);
+                None
+            }
+            Some(false) if x >= high => {
+                self.high = Some(true);
+                Some(true)
+            }
+            Some(true) if x <= low => {
+                self.high = Some(false);
+                Some(false)
+            }
+            _ => None,
+        }
+    }
+}
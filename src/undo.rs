@@ -0,0 +1,115 @@
+use crate::RingBuffer;
+
+// Bounded undo/redo over snapshots of the edited state. The ring holds the
+// current state, the undo states before it and the redo states after it;
+// recording drops the redo states and, once N states are kept, the oldest.
+#[derive(Debug, Clone)]
+pub struct UndoHistory<T, const N: usize>
+where
+    T: Copy,
+{
+    states: RingBuffer<T, N>,
+    len: usize,
+    redo: usize,
+    coalesce: Option<fn(&T, &T) -> bool>,
+    // whether the current state was recorded last and may be merged into
+    coalescable: bool,
+}
+
+impl<T, const N: usize> UndoHistory<T, N>
+where
+    T: Copy,
+{
+    pub const fn new(initial: T) -> Self {
+        assert!(N > 0, "N must be non zero");
+        UndoHistory {
+            states: RingBuffer::new(initial),
+            len: 1,
+            redo: 0,
+            coalesce: None,
+            coalescable: false,
+        }
+    }
+
+    // Consecutive records for which should_merge(current, new) is true
+    // replace the current state instead of adding one, so e.g. typing a word
+    // is undone at once. Undo, redo and checkpoint end a run.
+    pub const fn with_coalescing(initial: T, should_merge: fn(&T, &T) -> bool) -> Self {
+        let mut history = Self::new(initial);
+        history.coalesce = Some(should_merge);
+        history
+    }
+
+    pub fn record(&mut self, state: T) {
+        self.states.rewind(self.redo);
+        self.len -= self.redo;
+        self.redo = 0;
+        let merge = self.coalescable
+            && self
+                .coalesce
+                .is_some_and(|should_merge| should_merge(&self.current(), &state));
+        if merge {
+            self.states.rewind(1);
+        } else {
+            self.len = usize::min(self.len + 1, N);
+        }
+        self.states.put(state);
+        self.coalescable = true;
+    }
+
+    // Steps back and returns the state to restore, if there is an older one.
+    pub fn undo(&mut self) -> Option<T> {
+        if !self.can_undo() {
+            return None;
+        }
+        self.redo += 1;
+        self.coalescable = false;
+        Some(self.current())
+    }
+
+    pub fn redo(&mut self) -> Option<T> {
+        if !self.can_redo() {
+            return None;
+        }
+        self.redo -= 1;
+        self.coalescable = false;
+        Some(self.current())
+    }
+
+    pub fn current(&self) -> T {
+        self.states.get_newest(self.redo)
+    }
+
+    // the next record starts a new entry even if it could be merged
+    pub fn checkpoint(&mut self) {
+        self.coalescable = false;
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.undo_len() > 0
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.redo > 0
+    }
+
+    pub fn undo_len(&self) -> usize {
+        self.len - self.redo - 1
+    }
+
+    pub fn redo_len(&self) -> usize {
+        self.redo
+    }
+
+    // forgets everything but state
+    pub fn clear(&mut self, state: T) {
+        *self = UndoHistory {
+            coalesce: self.coalesce,
+            ..Self::new(state)
+        };
+    }
+
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+}